@@ -1,34 +1,110 @@
+use ocs_custodian::installer::{InstallError, InstallReport};
+use ocs_custodian::ocs_api::{is_store_download, ContentItem, OcsClient};
+use ocs_custodian::parser::check_url;
+use ocs_custodian::paths::PathResolver;
+use ocs_custodian::plan::InstallPlan;
+use ocs_custodian::progress::{Cancellation, InstallEvent};
+use ocs_custodian::ParsedOcsUrl;
 use relm4::{
     actions::{ActionGroupName, RelmAction, RelmActionGroup},
-    gtk, main_application, Component, ComponentController, ComponentParts, ComponentSender,
-    Controller, SimpleComponent,
+    adw, gtk, main_application, Component, ComponentController, ComponentParts, ComponentSender,
+    Controller,
 };
 
-use gtk::prelude::{ApplicationExt, ApplicationWindowExt, GtkWindowExt, SettingsExt, WidgetExt};
+use gettextrs::gettext;
+use gtk::prelude::{
+    ApplicationExt, ApplicationWindowExt, BoxExt, DialogExt, GtkWindowExt, OrientableExt,
+    SettingsExt, WidgetExt,
+};
 use gtk::{gio, glib};
+use std::collections::VecDeque;
 
+use crate::apply;
 use crate::config::{APP_ID, PROFILE};
+use crate::describe;
 use crate::modals::about::AboutDialog;
+use crate::modals::preferences::{PreferencesMsg, PreferencesWindow};
+use crate::pages::confirm::{ConfirmMsg, ConfirmOutput, ConfirmPage};
+use crate::pages::library::{LibraryMsg, LibraryOutput, LibraryPage};
+use crate::pages::progress::{ProgressMsg, ProgressOutput, ProgressPage};
+use crate::settings::InstallSettings;
 
 pub(super) struct App {
+    window: gtk::ApplicationWindow,
     about_dialog: Controller<AboutDialog>,
+    preferences_window: Controller<PreferencesWindow>,
+    confirm_page: Controller<ConfirmPage>,
+    progress_page: Controller<ProgressPage>,
+    library_page: Controller<LibraryPage>,
+    page: Page,
+    /// The link being looked at or installed.
+    current: Option<ParsedOcsUrl>,
+    /// Links that were opened while we were busy with another.
+    queue: VecDeque<ParsedOcsUrl>,
+    /// Stops the install that's running.
+    cancel: Option<Cancellation>,
+}
+
+/// What the window is showing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Welcome,
+    Checking,
+    Confirm,
+    Installing,
+}
+
+impl Page {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Welcome => "welcome",
+            Self::Checking => "working",
+            Self::Confirm => "confirm",
+            Self::Installing => "progress",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub(super) enum AppMsg {
+    /// An `ocs://` link that was opened with the app.
+    Open(String),
+    /// Goes through a link that was installed before again.
+    Reinstall(Box<ParsedOcsUrl>),
+    Install(Box<InstallPlan>),
+    /// Drops the current link and goes on to the next.
+    Cancel,
+    /// Stops the install that's running.
+    CancelInstall,
+    Toast(String),
+    Error {
+        heading: String,
+        body: String,
+    },
     Quit,
 }
 
+#[derive(Debug)]
+pub(super) enum AppCommand {
+    Planned(Result<Box<InstallPlan>, String>),
+    /// What the store says about a link's item.
+    StoreItem(Box<ParsedOcsUrl>, Box<ContentItem>),
+    Progress(InstallEvent),
+    Installed(Result<InstallReport, String>),
+    Cancelled,
+}
+
 relm4::new_action_group!(pub(super) WindowActionGroup, "win");
 relm4::new_stateless_action!(PreferencesAction, WindowActionGroup, "preferences");
 relm4::new_stateless_action!(pub(super) ShortcutsAction, WindowActionGroup, "show-help-overlay");
 relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
 
 #[relm4::component(pub)]
-impl SimpleComponent for App {
-    type Init = ();
+impl Component for App {
+    type Init = relm4::Receiver<String>;
     type Input = AppMsg;
     type Output = ();
+    type CommandOutput = AppCommand;
     type Widgets = AppWidgets;
 
     menu! {
@@ -66,21 +142,68 @@ impl SimpleComponent for App {
 
             #[wrap(Some)]
             set_titlebar = &gtk::HeaderBar {
+                #[wrap(Some)]
+                set_title_widget = &adw::ViewSwitcherTitle {
+                    set_title: &gettext("Amizade"),
+                    set_stack: Some(&view_stack),
+                },
+
                 pack_end = &gtk::MenuButton {
                     set_icon_name: "open-menu-symbolic",
                     set_menu_model: Some(&primary_menu),
                 }
             },
 
-            gtk::Label {
-                set_label: "Hello world!",
-                add_css_class: "title-header",
-            }
+            #[name = "toasts"]
+            adw::ToastOverlay {
+                #[name = "view_stack"]
+                adw::ViewStack {
+                    add_titled[Some("install"), &gettext("Install")] = &gtk::Stack {
+                        set_transition_type: gtk::StackTransitionType::Crossfade,
+
+                        add_named[Some("welcome")] = &adw::StatusPage {
+                            set_icon_name: Some(APP_ID),
+                            set_title: &gettext("Amizade"),
+                            set_description: Some(&gettext(
+                                "Click Install on a store like Pling, and it'll open here to be checked first",
+                            )),
+                        },
+
+                        add_named[Some("working")] = &gtk::Box {
+                            set_orientation: gtk::Orientation::Vertical,
+                            set_valign: gtk::Align::Center,
+                            set_spacing: 12,
+
+                            gtk::Spinner {
+                                set_spinning: true,
+                                set_size_request: (32, 32),
+                            },
+                            gtk::Label {
+                                add_css_class: "title-4",
+                                set_label: &gettext("Checking the link…"),
+                            },
+                        },
+
+                        add_named: (confirm_page, Some("confirm")),
+                        add_named: (progress_page, Some("progress")),
+
+                        #[watch]
+                        set_visible_child_name: model.page.name(),
+                    } -> {
+                        set_icon_name: Some("folder-download-symbolic"),
+                    },
+
+                    #[local_ref]
+                    add_titled[Some("library"), &gettext("Library")] = library_page -> gtk::Stack {} -> {
+                        set_icon_name: Some("view-grid-symbolic"),
+                    },
+                },
+            },
         }
     }
 
     fn init(
-        _init: Self::Init,
+        links: Self::Init,
         root: &Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
@@ -89,12 +212,62 @@ impl SimpleComponent for App {
             .launch(())
             .detach();
 
-        let model = Self { about_dialog };
+        let preferences_window = PreferencesWindow::builder()
+            .transient_for(root)
+            .launch(())
+            .detach();
 
+        let confirm_page = ConfirmPage::builder().launch(()).forward(
+            sender.input_sender(),
+            |output| match output {
+                ConfirmOutput::Install(plan) => AppMsg::Install(plan),
+                ConfirmOutput::Cancel => AppMsg::Cancel,
+            },
+        );
+
+        let progress_page = ProgressPage::builder().launch(()).forward(
+            sender.input_sender(),
+            |output| match output {
+                ProgressOutput::Cancel => AppMsg::CancelInstall,
+            },
+        );
+
+        let library_page = LibraryPage::builder().launch(()).forward(
+            sender.input_sender(),
+            |output| match output {
+                LibraryOutput::Reinstall(link) => AppMsg::Reinstall(link),
+                LibraryOutput::Toast(message) => AppMsg::Toast(message),
+                LibraryOutput::Error { heading, body } => AppMsg::Error { heading, body },
+            },
+        );
+
+        let model = Self {
+            window: root.clone(),
+            about_dialog,
+            preferences_window,
+            confirm_page,
+            progress_page,
+            library_page,
+            page: Page::Welcome,
+            current: None,
+            queue: VecDeque::new(),
+            cancel: None,
+        };
+
+        let confirm_page = model.confirm_page.widget();
+        let progress_page = model.progress_page.widget();
+        let library_page = model.library_page.widget();
         let widgets = view_output!();
 
         let actions = RelmActionGroup::<WindowActionGroup>::new();
 
+        let preferences_action = {
+            let sender = model.preferences_window.sender().clone();
+            RelmAction::<PreferencesAction>::new_stateless(move |_| {
+                sender.send(PreferencesMsg::Show).unwrap();
+            })
+        };
+
         let shortcuts_action = {
             let shortcuts = widgets.shortcuts.clone();
             RelmAction::<ShortcutsAction>::new_stateless(move |_| {
@@ -109,6 +282,7 @@ impl SimpleComponent for App {
             })
         };
 
+        actions.add_action(&preferences_action);
         actions.add_action(&shortcuts_action);
         actions.add_action(&about_action);
 
@@ -118,13 +292,114 @@ impl SimpleComponent for App {
 
         widgets.load_window_size();
 
+        {
+            let sender = sender.clone();
+            relm4::spawn_local(async move {
+                while let Some(link) = links.recv().await {
+                    sender.input(AppMsg::Open(link));
+                }
+            });
+        }
+
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
         match message {
+            AppMsg::Open(link) => match check_url(link) {
+                Ok(link) => self.open(link, widgets, &sender),
+                Err(e) => self.show_message(&gettext("That link can't be opened"), &e.to_string()),
+            },
+            AppMsg::Reinstall(link) => self.open(*link, widgets, &sender),
+            AppMsg::Install(plan) => self.install(plan, &sender),
+            AppMsg::Cancel => self.next(&sender),
+            AppMsg::CancelInstall => {
+                if let Some(cancel) = &self.cancel {
+                    cancel.cancel();
+                }
+            }
+            AppMsg::Toast(message) => widgets.toasts.add_toast(&adw::Toast::new(&message)),
+            AppMsg::Error { heading, body } => self.show_message(&heading, &body),
             AppMsg::Quit => main_application().quit(),
         }
+
+        self.update_view(widgets, sender);
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            AppCommand::Planned(Ok(plan)) => {
+                if InstallSettings::load().needs_confirmation(&plan) {
+                    self.look_up(&plan.link, &sender);
+                    self.confirm_page.emit(ConfirmMsg::Show(plan));
+                    self.page = Page::Confirm;
+                } else {
+                    self.install(plan, &sender);
+                }
+            }
+            AppCommand::Planned(Err(e)) => {
+                self.show_message(&gettext("Couldn't check what that link does"), &e);
+                self.next(&sender);
+            }
+            AppCommand::StoreItem(link, item) => {
+                // it could have been for a link that's gone by now
+                if self.page == Page::Confirm && self.current.as_ref() == Some(&*link) {
+                    self.confirm_page.emit(ConfirmMsg::StoreItem(item));
+                }
+            }
+            AppCommand::Progress(event) => self.progress_page.emit(ProgressMsg::Event(event)),
+            AppCommand::Installed(Ok(report)) => {
+                self.cancel = None;
+                self.library_page.emit(LibraryMsg::Reload);
+                for warning in &report.warnings {
+                    tracing::warn!("after installing: {warning}");
+                }
+                let applied = match InstallSettings::load().auto_apply {
+                    true => apply::apply(&report),
+                    false => Ok(false),
+                };
+                let message = match report.items.first().and_then(|item| item.file_name()) {
+                    Some(name) => match applied {
+                        Ok(true) => gettext("Installed and switched to {}"),
+                        Ok(false) => gettext("Installed {}"),
+                        Err(e) => {
+                            tracing::warn!("couldn't apply {}: {e}", name.to_string_lossy());
+                            gettext("Installed {}, but couldn't switch to it")
+                        }
+                    }
+                    .replacen("{}", &name.to_string_lossy(), 1),
+                    None => gettext("Nothing new was installed"),
+                };
+                widgets.toasts.add_toast(&adw::Toast::new(&message));
+                self.next(&sender);
+            }
+            AppCommand::Installed(Err(e)) => {
+                self.cancel = None;
+                self.show_message(&gettext("Couldn't install it"), &e);
+                self.next(&sender);
+            }
+            AppCommand::Cancelled => {
+                self.cancel = None;
+                widgets
+                    .toasts
+                    .add_toast(&adw::Toast::new(&gettext("The install was cancelled")));
+                self.next(&sender);
+            }
+        }
+
+        self.update_view(widgets, sender);
     }
 
     fn shutdown(&mut self, widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
@@ -132,6 +407,113 @@ impl SimpleComponent for App {
     }
 }
 
+impl App {
+    /// Queues a link up to be checked and confirmed, and shows it.
+    fn open(&mut self, link: ParsedOcsUrl, widgets: &AppWidgets, sender: &ComponentSender<Self>) {
+        self.window.present();
+        widgets.view_stack.set_visible_child_name("install");
+
+        self.queue.push_back(link);
+        if self.current.is_none() {
+            self.next(sender);
+        }
+    }
+
+    /// Goes on to the next link that was opened, if there is one.
+    fn next(&mut self, sender: &ComponentSender<Self>) {
+        self.current = self.queue.pop_front();
+
+        match self.current {
+            Some(_) => self.plan(sender),
+            None => self.page = Page::Welcome,
+        }
+    }
+
+    /// Works out what the current link would do, in the background. Nothing
+    /// is installed until the plan is confirmed.
+    fn plan(&mut self, sender: &ComponentSender<Self>) {
+        let Some(link) = self.current.clone() else {
+            return;
+        };
+        self.page = Page::Checking;
+
+        let settings = InstallSettings::load();
+        sender.spawn_oneshot_command(move || {
+            let planned = settings
+                .installer(link.install_type)
+                .map_err(|e| e.to_string())
+                .and_then(|installer| {
+                    installer
+                        .plan(&link, |_| ())
+                        .map(Box::new)
+                        .map_err(|e| e.to_string())
+                });
+            AppCommand::Planned(planned)
+        });
+    }
+
+    /// Asks the store about the item a link downloads, in the background, for
+    /// the confirm page to show. It's fine if it doesn't know.
+    fn look_up(&self, link: &ParsedOcsUrl, sender: &ComponentSender<Self>) {
+        if !is_store_download(&link.download_url) {
+            return;
+        }
+
+        let link = link.clone();
+        sender.spawn_command(
+            move |out| match OcsClient::default().content_for_link(&link) {
+                Ok(item) => out.emit(AppCommand::StoreItem(Box::new(link), Box::new(item))),
+                Err(e) => tracing::info!("couldn't look {} up: {e}", link.download_url),
+            },
+        );
+    }
+
+    /// Installs a confirmed plan in the background, from the download it
+    /// already has. The progress page hears about how it's going.
+    fn install(&mut self, plan: Box<InstallPlan>, sender: &ComponentSender<Self>) {
+        tracing::info!("installing {}", plan.link.download_url);
+        self.page = Page::Installing;
+        self.progress_page
+            .emit(ProgressMsg::Start(describe::plan_name(&plan)));
+
+        let cancel = Cancellation::new();
+        self.cancel = Some(cancel.clone());
+
+        let settings = InstallSettings::load();
+        sender.spawn_command(move |out| {
+            let installer = match settings.installer(plan.install_type) {
+                Ok(installer) => installer,
+                Err(e) => {
+                    plan.discard(&PathResolver::from_env());
+                    return out.emit(AppCommand::Installed(Err(e.to_string())));
+                }
+            };
+
+            let installed = installer.install_plan(&plan, &cancel, |event| {
+                out.emit(AppCommand::Progress(event))
+            });
+
+            out.emit(match installed {
+                Ok(report) => AppCommand::Installed(Ok(report)),
+                Err(InstallError::Cancelled) => AppCommand::Cancelled,
+                Err(e) => AppCommand::Installed(Err(e.to_string())),
+            });
+        });
+    }
+
+    fn show_message(&self, heading: &str, body: &str) {
+        let dialog = gtk::MessageDialog::builder()
+            .transient_for(&self.window)
+            .modal(true)
+            .buttons(gtk::ButtonsType::Close)
+            .text(heading)
+            .secondary_text(body)
+            .build();
+        dialog.connect_response(|dialog, _| dialog.close());
+        dialog.present();
+    }
+}
+
 impl AppWidgets {
     fn save_window_size(&self) -> Result<(), glib::BoolError> {
         let settings = gio::Settings::new(APP_ID);
@@ -0,0 +1,48 @@
+//! Switches the desktop over to things right after they're installed.
+use ocs_custodian::installer::InstallReport;
+use ocs_custodian::types::install_type::{InstallType, Styling};
+use relm4::gtk;
+
+use gtk::prelude::SettingsExt;
+use gtk::{gio, glib};
+
+/// The GNOME setting that picks each install type that can be applied.
+fn setting(install_type: InstallType) -> Option<(&'static str, &'static str)> {
+    match install_type {
+        InstallType::Styling(Styling::GTK3Themes) => {
+            Some(("org.gnome.desktop.interface", "gtk-theme"))
+        }
+        InstallType::Styling(Styling::Icons) => Some(("org.gnome.desktop.interface", "icon-theme")),
+        InstallType::Styling(Styling::Cursors) => {
+            Some(("org.gnome.desktop.interface", "cursor-theme"))
+        }
+        _ => None,
+    }
+}
+
+/// Applies what an install put in place, if it's a theme we know how to switch
+/// to. Returns whether it was applied.
+pub(crate) fn apply(report: &InstallReport) -> Result<bool, glib::BoolError> {
+    let Some((schema, key)) = setting(report.install_type) else {
+        return Ok(false);
+    };
+    // themes are folders named after themselves
+    let Some(name) = report
+        .items
+        .first()
+        .filter(|item| item.is_dir())
+        .and_then(|item| item.file_name())
+    else {
+        return Ok(false);
+    };
+
+    let installed = gio::SettingsSchemaSource::default()
+        .and_then(|source| source.lookup(schema, true))
+        .is_some();
+    if !installed {
+        return Ok(false);
+    }
+
+    gio::Settings::new(schema).set_string(key, &name.to_string_lossy())?;
+    Ok(true)
+}
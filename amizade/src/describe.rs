@@ -0,0 +1,71 @@
+//! Puts ocs-custodian's types into words for people.
+use gettextrs::gettext;
+use ocs_custodian::installer::{Conflict, ConflictPolicy};
+use ocs_custodian::plan::InstallPlan;
+use ocs_custodian::types::install_type::{InstallCategory, InstallType};
+use relm4::gtk;
+
+/// What a plan would install, by the name of the first thing it puts in place.
+pub(crate) fn plan_name(plan: &InstallPlan) -> String {
+    match plan.items.first().and_then(|item| item.file_name()) {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => plan.link.download_filename().unwrap_or_default(),
+    }
+}
+
+/// Like "gtk3 themes". Aliases are the closest thing to a name each type has.
+pub(crate) fn install_type(install_type: InstallType) -> String {
+    install_type.aliases()[0].replace('_', " ")
+}
+
+pub(crate) fn category(category: InstallCategory) -> String {
+    match category {
+        InstallCategory::PersonalMedia => gettext("Personal Media"),
+        InstallCategory::Styling => gettext("Styling"),
+        InstallCategory::WMThemes => gettext("Window Manager Themes"),
+        InstallCategory::QtGeneral => gettext("Qt and KDE"),
+        InstallCategory::AppSpecific => gettext("App Add-ons"),
+    }
+}
+
+/// What the policy does, as an option to pick.
+pub(crate) fn conflict_policy(policy: ConflictPolicy) -> String {
+    match policy {
+        ConflictPolicy::Skip => gettext("Keep the installed one"),
+        ConflictPolicy::Overwrite => gettext("Replace it"),
+        ConflictPolicy::KeepBoth => gettext("Keep both"),
+        ConflictPolicy::Backup => gettext("Back up the installed one"),
+    }
+}
+
+/// The policies' descriptions, in the order of [`ConflictPolicy::ALL`].
+pub(crate) fn policy_list() -> gtk::StringList {
+    let names = ConflictPolicy::ALL.map(conflict_policy);
+    gtk::StringList::new(&names.each_ref().map(String::as_str))
+}
+
+/// What'll happen to something that's in the way.
+pub(crate) fn conflict(conflict: &Conflict) -> String {
+    let existing = conflict.existing.display().to_string();
+
+    match (conflict.policy, &conflict.moved_to) {
+        (ConflictPolicy::Skip, _) => {
+            gettext("{} is already there, so nothing new is installed in its place")
+                .replacen("{}", &existing, 1)
+        }
+        (ConflictPolicy::Overwrite, _) => {
+            gettext("{} is already there, and will be replaced").replacen("{}", &existing, 1)
+        }
+        (ConflictPolicy::KeepBoth, Some(to)) => {
+            gettext("{} is already there, so the new one goes to {}")
+                .replacen("{}", &existing, 1)
+                .replacen("{}", &to.display().to_string(), 1)
+        }
+        (ConflictPolicy::Backup, Some(to)) => {
+            gettext("{} is already there, and will be moved to {}")
+                .replacen("{}", &existing, 1)
+                .replacen("{}", &to.display().to_string(), 1)
+        }
+        (_, None) => gettext("{} is already there").replacen("{}", &existing, 1),
+    }
+}
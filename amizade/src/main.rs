@@ -1,10 +1,15 @@
 #[rustfmt::skip]
 mod config;
 mod app;
+mod apply;
+mod describe;
 mod modals;
+mod pages;
+mod settings;
 mod setup;
 
-use gtk::prelude::ApplicationExt;
+use gtk::gio;
+use gtk::prelude::{ApplicationExt, ApplicationExtManual, FileExt};
 use relm4::{
     actions::{AccelsPlus, RelmAction, RelmActionGroup},
     gtk, main_application, RelmApp,
@@ -13,7 +18,6 @@ use relm4::{
 use app::App;
 use setup::setup;
 
-#[allow(unused)] // TODO: remove?
 use crate::config::APP_ID;
 
 relm4::new_action_group!(AppActionGroup, "app");
@@ -29,6 +33,8 @@ fn main() {
     setup();
 
     let app = main_application();
+    app.set_application_id(Some(APP_ID));
+    app.set_flags(gio::ApplicationFlags::HANDLES_OPEN);
     app.set_resource_base_path(Some("/club/barretts/Amizade/"));
 
     let actions = RelmActionGroup::<AppActionGroup>::new();
@@ -45,7 +51,18 @@ fn main() {
 
     app.set_action_group(Some(&actions.into_action_group()));
 
+    // links from `amizade %u` (or from a second `amizade` call, which hands
+    // them to this one) arrive as files. the window picks them up once it's open
+    let (links, link_receiver) = relm4::channel::<String>();
+    app.connect_open(move |app, files, _hint| {
+        for file in files {
+            links.emit(file.uri().into());
+        }
+        app.activate();
+    });
+
     let app = RelmApp::with_app(app);
 
-    app.run::<App>(());
+    let args: Vec<String> = std::env::args().collect();
+    app.run_with_args::<App, _>(link_receiver, &args);
 }
@@ -0,0 +1,419 @@
+use ocs_custodian::config::Config;
+use ocs_custodian::installer::ConflictPolicy;
+use ocs_custodian::paths::PathResolver;
+use ocs_custodian::types::install_type::InstallType;
+use relm4::{adw, gtk, ComponentParts, ComponentSender, SimpleComponent};
+
+use adw::prelude::{
+    ActionRowExt, ComboRowExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
+    PreferencesWindowExt,
+};
+use gettextrs::gettext;
+use gtk::gio;
+use gtk::prelude::{
+    ButtonExt, EditableExt, EntryExt, FileChooserExt, FileExt, GtkWindowExt, NativeDialogExt,
+    SettingsExt, SettingsExtManual, WidgetExt,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::APP_ID;
+use crate::describe;
+use crate::settings::Confirm;
+
+/// Changes the settings in `club.barretts.Amizade`. Everything is saved as
+/// soon as it's changed.
+pub struct PreferencesWindow {
+    window: adw::PreferencesWindow,
+    settings: gio::Settings,
+    /// Each install type's row, and the button that undoes its override.
+    destination_rows: Vec<(InstallType, adw::ActionRow, gtk::Button)>,
+    hosts: adw::PreferencesGroup,
+    host_rows: Vec<adw::ActionRow>,
+    /// Kept around while it's open, since it'd close when dropped.
+    chooser: Option<gtk::FileChooserNative>,
+}
+
+#[derive(Debug)]
+pub enum PreferencesMsg {
+    Show,
+    SelectPolicy(u32),
+    SelectConfirm(u32),
+    ChooseDestination(InstallType),
+    /// Sends an install type somewhere else, or back to its default.
+    SetDestination(InstallType, Option<PathBuf>),
+    AddHost(String),
+    RemoveHost(String),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for PreferencesWindow {
+    type Init = ();
+    type Input = PreferencesMsg;
+    type Output = ();
+    type Widgets = PreferencesWindowWidgets;
+
+    view! {
+        adw::PreferencesWindow {
+            set_modal: true,
+            set_hide_on_close: true,
+
+            add = &adw::PreferencesPage {
+                set_title: &gettext("General"),
+                set_icon_name: Some("preferences-system-symbolic"),
+
+                add = &adw::PreferencesGroup {
+                    set_title: &gettext("Installing"),
+
+                    adw::ComboRow {
+                        set_title: &gettext("When Something's in the Way"),
+                        set_subtitle: &gettext("Installs can still pick something else"),
+                        set_model: Some(&describe::policy_list()),
+                        set_selected: policy_index(&settings),
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(PreferencesMsg::SelectPolicy(row.selected()));
+                        },
+                    },
+
+                    adw::ActionRow {
+                        set_title: &gettext("Apply After Installing"),
+                        set_subtitle: &gettext("Switch to GTK themes, icons and cursors once they're installed"),
+                        set_activatable_widget: Some(&auto_apply),
+
+                        #[name = "auto_apply"]
+                        add_suffix = &gtk::Switch {
+                            set_valign: gtk::Align::Center,
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &gettext("Asking First"),
+
+                    adw::ComboRow {
+                        set_title: &gettext("Ask Before Installing"),
+                        set_subtitle: &gettext("Installs that would replace something are always asked about"),
+                        set_model: Some(&confirm_list()),
+                        set_selected: confirm_index(&settings),
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(PreferencesMsg::SelectConfirm(row.selected()));
+                        },
+                    },
+                },
+
+                #[local_ref]
+                add = hosts -> adw::PreferencesGroup {
+                    set_title: &gettext("Trusted Hosts"),
+                    set_description: Some(&gettext("Links that download from these, or their subdomains, count as trusted")),
+
+                    adw::ActionRow {
+                        set_title: &gettext("Add a Host"),
+
+                        add_suffix = &gtk::Entry {
+                            set_valign: gtk::Align::Center,
+                            set_hexpand: true,
+                            set_placeholder_text: Some("files.example.com"),
+                            set_secondary_icon_name: Some("list-add-symbolic"),
+                            set_secondary_icon_tooltip_text: Some(&gettext("Add")),
+                            connect_activate[sender] => move |entry| {
+                                sender.input(PreferencesMsg::AddHost(entry.text().into()));
+                                entry.set_text("");
+                            },
+                            connect_icon_press => |entry, _| entry.emit_activate(),
+                        },
+                    },
+                },
+            },
+
+            #[local_ref]
+            add = destinations -> adw::PreferencesPage {
+                set_title: &gettext("Destinations"),
+                set_icon_name: Some("folder-symbolic"),
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let settings = gio::Settings::new(APP_ID);
+        let hosts = adw::PreferencesGroup::new();
+        let destinations = adw::PreferencesPage::new();
+
+        let mut groups: Vec<(_, adw::PreferencesGroup)> = Vec::new();
+        let mut destination_rows = Vec::new();
+        // these are sorted by category already
+        for install_type in InstallType::all() {
+            let category = install_type.category();
+            if groups.last().map(|(c, _)| *c) != Some(category) {
+                let group = adw::PreferencesGroup::builder()
+                    .title(&describe::category(category))
+                    .build();
+                destinations.add(&group);
+                groups.push((category, group));
+            }
+
+            let (row, reset) = destination_row(install_type, &sender);
+            groups.last().unwrap().1.add(&row);
+            destination_rows.push((install_type, row, reset));
+        }
+
+        let mut model = Self {
+            window: root.clone(),
+            settings: settings.clone(),
+            destination_rows,
+            hosts: hosts.clone(),
+            host_rows: Vec::new(),
+            chooser: None,
+        };
+        model.refresh_destinations();
+        model.refresh_hosts(&sender);
+
+        let hosts = &hosts;
+        let destinations = &destinations;
+        let widgets = view_output!();
+
+        settings
+            .bind("auto-apply", &widgets.auto_apply, "active")
+            .build();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            PreferencesMsg::Show => self.window.present(),
+            PreferencesMsg::SelectPolicy(index) => {
+                if let Some(policy) = ConflictPolicy::ALL.get(index as usize) {
+                    self.save(|settings| {
+                        settings.set_string("conflict-policy", &policy.to_string())
+                    });
+                }
+            }
+            PreferencesMsg::SelectConfirm(index) => {
+                if let Some(confirm) = Confirm::ALL.get(index as usize) {
+                    self.save(|settings| settings.set_string("confirm-installs", confirm.key()));
+                }
+            }
+            PreferencesMsg::ChooseDestination(install_type) => {
+                self.choose_destination(install_type, &sender)
+            }
+            PreferencesMsg::SetDestination(install_type, path) => {
+                self.chooser = None;
+                self.set_destination(install_type, path);
+            }
+            PreferencesMsg::AddHost(host) => {
+                let host = host.trim().trim_end_matches('.').to_lowercase();
+                if host.is_empty() || host.contains(|c: char| c == '/' || c.is_whitespace()) {
+                    self.toast(&gettext("That isn't a host, like files.example.com"));
+                    return;
+                }
+
+                let mut hosts = self.trusted_hosts();
+                if !hosts.contains(&host) {
+                    hosts.push(host);
+                    self.save_hosts(&hosts, &sender);
+                }
+            }
+            PreferencesMsg::RemoveHost(host) => {
+                let mut hosts = self.trusted_hosts();
+                hosts.retain(|trusted| *trusted != host);
+                self.save_hosts(&hosts, &sender);
+            }
+        }
+    }
+}
+
+impl PreferencesWindow {
+    fn toast(&self, message: &str) {
+        self.window.add_toast(&adw::Toast::new(message));
+    }
+
+    /// Writes a setting, and says so if it couldn't be.
+    fn save(&self, write: impl FnOnce(&gio::Settings) -> Result<(), gtk::glib::BoolError>) {
+        if let Err(e) = write(&self.settings) {
+            self.toast(&gettext("Couldn't save that: {}").replacen("{}", &e.to_string(), 1));
+        }
+    }
+
+    fn destinations(&self) -> HashMap<String, String> {
+        self.settings.get("destinations")
+    }
+
+    fn choose_destination(&mut self, install_type: InstallType, sender: &ComponentSender<Self>) {
+        let chooser = gtk::FileChooserNative::new(
+            Some(&gettext("Install {} To").replacen(
+                "{}",
+                &describe::install_type(install_type),
+                1,
+            )),
+            Some(&self.window),
+            gtk::FileChooserAction::SelectFolder,
+            Some(&gettext("_Select")),
+            None,
+        );
+
+        let sender = sender.clone();
+        chooser.connect_response(move |chooser, response| {
+            let path = chooser.file().and_then(|file| file.path());
+            match path.filter(|_| response == gtk::ResponseType::Accept) {
+                Some(path) => {
+                    sender.input(PreferencesMsg::SetDestination(install_type, Some(path)))
+                }
+                None => chooser.destroy(),
+            }
+        });
+        chooser.show();
+
+        self.chooser = Some(chooser);
+    }
+
+    fn set_destination(&mut self, install_type: InstallType, path: Option<PathBuf>) {
+        let alias = install_type.aliases()[0];
+        let mut destinations = self.destinations();
+
+        match path {
+            Some(path) => {
+                let path = path.display().to_string();
+                // the same checks a config file gets
+                let checked =
+                    Config::default().set_type_override(alias, &path, &PathResolver::from_env());
+                if let Err(e) = checked {
+                    self.toast(&e.to_string());
+                    return;
+                }
+                destinations.insert(alias.to_owned(), path);
+            }
+            None => {
+                destinations.remove(alias);
+            }
+        }
+
+        self.save(|settings| settings.set("destinations", &destinations));
+        self.refresh_destinations();
+    }
+
+    /// Shows where each install type goes now.
+    fn refresh_destinations(&self) {
+        let resolver = PathResolver::from_env();
+        let config = Config::load(&resolver).unwrap_or_default();
+        let destinations = self.destinations();
+
+        for (install_type, row, reset) in &self.destination_rows {
+            let overridden = destinations.get(install_type.aliases()[0]);
+            let destination = match overridden {
+                Some(path) => path.clone(),
+                None => config
+                    .destination(install_type, &resolver)
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+            };
+
+            row.set_subtitle(&destination);
+            reset.set_visible(overridden.is_some());
+        }
+    }
+
+    fn trusted_hosts(&self) -> Vec<String> {
+        self.settings
+            .strv("trusted-hosts")
+            .iter()
+            .map(|host| host.to_string())
+            .collect()
+    }
+
+    fn save_hosts(&mut self, hosts: &[String], sender: &ComponentSender<Self>) {
+        let hosts: Vec<&str> = hosts.iter().map(String::as_str).collect();
+        self.save(|settings| settings.set_strv("trusted-hosts", &hosts));
+        self.refresh_hosts(sender);
+    }
+
+    /// Makes a row for each trusted host, after the row that adds them.
+    fn refresh_hosts(&mut self, sender: &ComponentSender<Self>) {
+        for row in self.host_rows.drain(..) {
+            self.hosts.remove(&row);
+        }
+
+        for host in self.trusted_hosts() {
+            let row = adw::ActionRow::builder().title(&host).build();
+
+            let remove = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(&gettext("Remove"))
+                .valign(gtk::Align::Center)
+                .css_classes(vec!["flat".to_owned()])
+                .build();
+            let sender = sender.clone();
+            remove.connect_clicked(move |_| sender.input(PreferencesMsg::RemoveHost(host.clone())));
+            row.add_suffix(&remove);
+
+            self.hosts.add(&row);
+            self.host_rows.push(row);
+        }
+    }
+}
+
+/// A row that shows where an install type goes, and lets it be changed.
+/// Returns it with the button that puts it back to the default.
+fn destination_row(
+    install_type: InstallType,
+    sender: &ComponentSender<PreferencesWindow>,
+) -> (adw::ActionRow, gtk::Button) {
+    let row = adw::ActionRow::builder()
+        .title(&describe::install_type(install_type))
+        .activatable(true)
+        .build();
+
+    let reset = gtk::Button::builder()
+        .icon_name("edit-undo-symbolic")
+        .tooltip_text(&gettext("Use the Default"))
+        .valign(gtk::Align::Center)
+        .css_classes(vec!["flat".to_owned()])
+        .build();
+    {
+        let sender = sender.clone();
+        reset.connect_clicked(move |_| {
+            sender.input(PreferencesMsg::SetDestination(install_type, None));
+        });
+    }
+    row.add_suffix(&reset);
+    row.add_suffix(&gtk::Image::from_icon_name("folder-open-symbolic"));
+
+    let sender = sender.clone();
+    row.connect_activated(move |_| {
+        sender.input(PreferencesMsg::ChooseDestination(install_type));
+    });
+
+    (row, reset)
+}
+
+fn policy_index(settings: &gio::Settings) -> u32 {
+    let policy =
+        ConflictPolicy::try_from(settings.string("conflict-policy").as_str()).unwrap_or_default();
+
+    ConflictPolicy::ALL
+        .iter()
+        .position(|p| *p == policy)
+        .unwrap_or_default() as u32
+}
+
+fn confirm_index(settings: &gio::Settings) -> u32 {
+    let key = settings.string("confirm-installs");
+
+    Confirm::ALL
+        .iter()
+        .position(|confirm| confirm.key() == key)
+        .unwrap_or_default() as u32
+}
+
+/// The choices for when to ask, in the order of [`Confirm::ALL`].
+fn confirm_list() -> gtk::StringList {
+    let names = Confirm::ALL.map(|confirm| match confirm {
+        Confirm::Always => gettext("Every time"),
+        Confirm::Untrusted => gettext("Unless the host is trusted"),
+        Confirm::Never => gettext("Never"),
+    });
+    gtk::StringList::new(&names.each_ref().map(String::as_str))
+}
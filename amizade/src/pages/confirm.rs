@@ -0,0 +1,332 @@
+use ocs_custodian::installer::ConflictPolicy;
+use ocs_custodian::ocs_api::ContentItem;
+use ocs_custodian::paths::PathResolver;
+use ocs_custodian::plan::{InstallPlan, PostInstallAction};
+use relm4::{adw, gtk, ComponentParts, ComponentSender, SimpleComponent};
+
+use adw::prelude::{ActionRowExt, ComboRowExt, PreferencesGroupExt, PreferencesRowExt};
+use gettextrs::gettext;
+use gtk::glib;
+use gtk::prelude::{BoxExt, ButtonExt, OrientableExt, WidgetExt};
+use relm4::RelmWidgetExt;
+
+use crate::describe;
+
+/// Shows what a link will do, and asks before doing it.
+pub struct ConfirmPage {
+    plan: Option<Box<InstallPlan>>,
+    /// What the store says about it, once it's been asked.
+    store_item: Option<Box<ContentItem>>,
+}
+
+#[derive(Debug)]
+pub enum ConfirmMsg {
+    Show(Box<InstallPlan>),
+    StoreItem(Box<ContentItem>),
+    SelectPolicy(u32),
+    Install,
+    Cancel,
+}
+
+#[derive(Debug)]
+pub enum ConfirmOutput {
+    Install(Box<InstallPlan>),
+    Cancel,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for ConfirmPage {
+    type Init = ();
+    type Input = ConfirmMsg;
+    type Output = ConfirmOutput;
+    type Widgets = ConfirmPageWidgets;
+
+    view! {
+        gtk::ScrolledWindow {
+            set_hscrollbar_policy: gtk::PolicyType::Never,
+
+            adw::Clamp {
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 24,
+                    set_margin_all: 24,
+
+                    gtk::Label {
+                        add_css_class: "title-1",
+                        set_wrap: true,
+                        #[watch]
+                        set_label: &gettext("Install {}?").replacen("{}", &model.name(), 1),
+                    },
+
+                    adw::PreferencesGroup {
+                        adw::ActionRow {
+                            set_title: &gettext("From"),
+                            #[watch]
+                            set_subtitle: &model.host(),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("File"),
+                            #[watch]
+                            set_subtitle: &model.filename(),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("Type"),
+                            #[watch]
+                            set_subtitle: &model.install_type(),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("Into"),
+                            #[watch]
+                            set_subtitle: &model.destination(),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("Size"),
+                            #[watch]
+                            set_subtitle: &model.size(),
+                        },
+                    },
+
+                    adw::PreferencesGroup {
+                        set_title: &gettext("From the Store"),
+                        #[watch]
+                        set_visible: model.store_item.is_some(),
+
+                        adw::ActionRow {
+                            set_title: &gettext("Name"),
+                            #[watch]
+                            set_subtitle: &model.store_field(|item| Some(item.name.clone())),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("Author"),
+                            #[watch]
+                            set_subtitle: &model.store_field(|item| item.author.clone()),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("Version"),
+                            #[watch]
+                            set_subtitle: &model.store_field(|item| item.version.clone()),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("License"),
+                            #[watch]
+                            set_subtitle: &model.store_field(|item| item.license.clone()),
+                        },
+                        adw::ActionRow {
+                            set_title: &gettext("Screenshots"),
+                            #[watch]
+                            set_subtitle: &model.screenshots(),
+                            #[watch]
+                            set_visible: !model.screenshot_uri().is_empty(),
+
+                            add_suffix = &gtk::LinkButton {
+                                set_label: &gettext("Open"),
+                                set_valign: gtk::Align::Center,
+                                #[watch]
+                                set_uri: &model.screenshot_uri(),
+                            },
+                        },
+                    },
+
+                    adw::PreferencesGroup {
+                        set_title: &gettext("Already Installed"),
+                        #[watch]
+                        set_description: Some(&model.conflicts()),
+                        #[watch]
+                        set_visible: model.has_conflicts(),
+
+                        adw::ComboRow {
+                            set_title: &gettext("When Something's in the Way"),
+                            set_model: Some(&describe::policy_list()),
+                            #[watch]
+                            #[block_signal(policy_handler)]
+                            set_selected: model.policy_index(),
+
+                            connect_selected_notify[sender] => move |row| {
+                                sender.input(ConfirmMsg::SelectPolicy(row.selected()));
+                            } @policy_handler,
+                        },
+                    },
+
+                    gtk::Box {
+                        set_halign: gtk::Align::Center,
+                        set_spacing: 12,
+                        set_homogeneous: true,
+
+                        gtk::Button {
+                            set_label: &gettext("_Cancel"),
+                            set_use_underline: true,
+                            add_css_class: "pill",
+                            connect_clicked => ConfirmMsg::Cancel,
+                        },
+                        gtk::Button {
+                            set_label: &gettext("_Install"),
+                            set_use_underline: true,
+                            add_css_class: "pill",
+                            add_css_class: "suggested-action",
+                            connect_clicked => ConfirmMsg::Install,
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            plan: None,
+            store_item: None,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            ConfirmMsg::Show(plan) => {
+                self.plan = Some(plan);
+                self.store_item = None;
+            }
+            ConfirmMsg::StoreItem(item) => self.store_item = Some(item),
+            ConfirmMsg::SelectPolicy(index) => {
+                let policy = ConflictPolicy::ALL.get(index as usize).copied();
+                if let (Some(plan), Some(policy)) = (&mut self.plan, policy) {
+                    if plan.conflict_policy != policy {
+                        plan.set_conflict_policy(policy);
+                    }
+                }
+            }
+            ConfirmMsg::Install => {
+                if let Some(plan) = self.plan.take() {
+                    sender.output(ConfirmOutput::Install(plan)).unwrap();
+                }
+            }
+            ConfirmMsg::Cancel => {
+                if let Some(plan) = self.plan.take() {
+                    plan.discard(&PathResolver::from_env());
+                }
+                sender.output(ConfirmOutput::Cancel).unwrap();
+            }
+        }
+    }
+}
+
+impl ConfirmPage {
+    fn name(&self) -> String {
+        self.plan
+            .as_deref()
+            .map(describe::plan_name)
+            .unwrap_or_default()
+    }
+
+    fn host(&self) -> String {
+        self.plan
+            .as_ref()
+            .and_then(|plan| plan.link.download_url.host_str().map(str::to_owned))
+            .unwrap_or_default()
+    }
+
+    fn filename(&self) -> String {
+        self.plan
+            .as_ref()
+            .and_then(|plan| plan.link.download_filename().ok())
+            .unwrap_or_default()
+    }
+
+    fn install_type(&self) -> String {
+        let Some(plan) = &self.plan else {
+            return String::new();
+        };
+
+        format!(
+            "{} ({})",
+            describe::install_type(plan.install_type),
+            describe::category(plan.install_type.category())
+        )
+    }
+
+    fn destination(&self) -> String {
+        self.plan
+            .as_ref()
+            .map(|plan| plan.destination.display().to_string())
+            .unwrap_or_default()
+    }
+
+    fn size(&self) -> String {
+        let Some(plan) = &self.plan else {
+            return String::new();
+        };
+
+        let mut size = gettext("{} in {} files, from a {} download")
+            .replacen("{}", &glib::format_size(plan.install_size), 1)
+            .replacen("{}", &plan.files.len().to_string(), 1)
+            .replacen("{}", &glib::format_size(plan.download_size), 1);
+        for action in &plan.post_install {
+            match action {
+                PostInstallAction::UpdateFontCache => {
+                    size.push_str(&gettext(", then the font cache is updated"))
+                }
+            }
+        }
+
+        size
+    }
+
+    fn store_field(&self, field: impl Fn(&ContentItem) -> Option<String>) -> String {
+        self.store_item
+            .as_deref()
+            .and_then(field)
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| gettext("Unknown"))
+    }
+
+    fn screenshots(&self) -> String {
+        let count = self
+            .store_item
+            .as_ref()
+            .map_or(0, |item| item.previews.len());
+
+        gettext("{} on the store").replacen("{}", &count.to_string(), 1)
+    }
+
+    fn screenshot_uri(&self) -> String {
+        self.store_item
+            .as_ref()
+            .and_then(|item| item.previews.first())
+            .map(|url| url.to_string())
+            .unwrap_or_default()
+    }
+
+    fn has_conflicts(&self) -> bool {
+        self.plan
+            .as_ref()
+            .is_some_and(|plan| !plan.conflicts.is_empty())
+    }
+
+    fn conflicts(&self) -> String {
+        self.plan
+            .iter()
+            .flat_map(|plan| &plan.conflicts)
+            .map(describe::conflict)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn policy_index(&self) -> u32 {
+        let policy = self
+            .plan
+            .as_ref()
+            .map(|plan| plan.conflict_policy)
+            .unwrap_or_default();
+
+        ConflictPolicy::ALL
+            .iter()
+            .position(|p| *p == policy)
+            .unwrap_or_default() as u32
+    }
+}
@@ -0,0 +1,374 @@
+use ocs_custodian::installer::{Installer, UninstallError, UninstallReport};
+use ocs_custodian::manifest::{InstalledItem, Manifest};
+use ocs_custodian::paths::PathResolver;
+use ocs_custodian::types::install_type::InstallCategory;
+use ocs_custodian::ParsedOcsUrl;
+use relm4::factory::{DynamicIndex, FactoryComponent, FactorySender, FactoryVecDeque};
+use relm4::{adw, gtk, Component, ComponentParts, ComponentSender};
+
+use adw::prelude::{ActionRowExt, ExpanderRowExt, PreferencesPageExt, PreferencesRowExt};
+use gettextrs::gettext;
+use gtk::prelude::{BoxExt, ButtonExt, Cast, DialogExt, FileExt, GtkWindowExt, WidgetExt};
+use gtk::{gio, glib};
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use crate::describe;
+
+/// The categories, in the order they're shown.
+const CATEGORIES: [InstallCategory; 5] = [
+    InstallCategory::Styling,
+    InstallCategory::WMThemes,
+    InstallCategory::QtGeneral,
+    InstallCategory::AppSpecific,
+    InstallCategory::PersonalMedia,
+];
+
+/// Everything that was installed, grouped by category.
+pub struct LibraryPage {
+    /// One list for each of [`CATEGORIES`].
+    groups: Vec<(adw::PreferencesGroup, FactoryVecDeque<LibraryRow>)>,
+    is_empty: bool,
+}
+
+#[derive(Debug)]
+pub enum LibraryMsg {
+    /// Reads the installed items again.
+    Reload,
+    OpenFolder(u64),
+    Reinstall(u64),
+    Remove(u64),
+    /// Removes an item even if its files changed since it was installed.
+    ForceRemove(u64),
+}
+
+#[derive(Debug)]
+pub enum LibraryOutput {
+    /// Goes through the link again, like it was just opened.
+    Reinstall(Box<ParsedOcsUrl>),
+    Toast(String),
+    Error {
+        heading: String,
+        body: String,
+    },
+}
+
+#[derive(Debug)]
+pub enum LibraryCommand {
+    Removed(Result<UninstallReport, String>),
+    /// Some of the item's files changed since it was installed, so it's still
+    /// there.
+    Modified(u64, Vec<PathBuf>),
+}
+
+#[relm4::component(pub)]
+impl Component for LibraryPage {
+    type Init = ();
+    type Input = LibraryMsg;
+    type Output = LibraryOutput;
+    type CommandOutput = LibraryCommand;
+    type Widgets = LibraryPageWidgets;
+
+    view! {
+        gtk::Stack {
+            add_named[Some("empty")] = &adw::StatusPage {
+                set_icon_name: Some("folder-symbolic"),
+                set_title: &gettext("Nothing Installed Yet"),
+                set_description: Some(&gettext("Things installed with Amizade show up here")),
+            },
+
+            #[local_ref]
+            add_named[Some("items")] = items -> adw::PreferencesPage {},
+
+            #[watch]
+            set_visible_child_name: if model.is_empty { "empty" } else { "items" },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let items = adw::PreferencesPage::new();
+
+        let groups = CATEGORIES
+            .iter()
+            .map(|category| {
+                let group = adw::PreferencesGroup::builder()
+                    .title(&describe::category(*category))
+                    .build();
+                items.add(&group);
+
+                let rows = FactoryVecDeque::new(group.clone(), sender.input_sender());
+                (group, rows)
+            })
+            .collect();
+
+        let mut model = Self {
+            groups,
+            is_empty: true,
+        };
+        model.reload(&sender);
+
+        let items = &items;
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
+        match message {
+            LibraryMsg::Reload => self.reload(&sender),
+            LibraryMsg::OpenFolder(id) => {
+                if let Some(item) = self.item(id) {
+                    let folder = match item.items.first().filter(|item| item.is_dir()) {
+                        Some(dir) => dir.clone(),
+                        None => item.destination.clone(),
+                    };
+
+                    let uri = gio::File::for_path(&folder).uri();
+                    if let Err(e) =
+                        gio::AppInfo::launch_default_for_uri(&uri, None::<&gio::AppLaunchContext>)
+                    {
+                        sender
+                            .output(LibraryOutput::Error {
+                                heading: gettext("Couldn't open the folder"),
+                                body: e.to_string(),
+                            })
+                            .unwrap();
+                    }
+                }
+            }
+            LibraryMsg::Reinstall(id) => {
+                if let Some(item) = self.item(id) {
+                    let link = Box::new(item.link.clone());
+                    sender.output(LibraryOutput::Reinstall(link)).unwrap();
+                }
+            }
+            LibraryMsg::Remove(id) => self.remove(id, false, &sender),
+            LibraryMsg::ForceRemove(id) => self.remove(id, true, &sender),
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            LibraryCommand::Removed(Ok(report)) => {
+                let message = match report.kept.is_empty() {
+                    true => gettext("Removed {} files").replacen(
+                        "{}",
+                        &report.removed.len().to_string(),
+                        1,
+                    ),
+                    false => {
+                        gettext("Removed {} files, but kept folders with other things in them")
+                            .replacen("{}", &report.removed.len().to_string(), 1)
+                    }
+                };
+                sender.output(LibraryOutput::Toast(message)).unwrap();
+            }
+            LibraryCommand::Removed(Err(e)) => {
+                sender
+                    .output(LibraryOutput::Error {
+                        heading: gettext("Couldn't remove it"),
+                        body: e,
+                    })
+                    .unwrap();
+            }
+            LibraryCommand::Modified(id, files) => {
+                ask_to_force_remove(root, id, &files, &sender);
+            }
+        }
+
+        self.reload(&sender);
+    }
+}
+
+impl LibraryPage {
+    /// Fills the lists from the manifest.
+    fn reload(&mut self, sender: &ComponentSender<Self>) {
+        let manifest = match Manifest::load(&PathResolver::from_env()) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                sender
+                    .output(LibraryOutput::Error {
+                        heading: gettext("Couldn't read what's installed"),
+                        body: e.to_string(),
+                    })
+                    .unwrap();
+                return;
+            }
+        };
+
+        for (category, (group, rows)) in CATEGORIES.iter().zip(&mut self.groups) {
+            let mut rows = rows.guard();
+            rows.clear();
+
+            // newest first
+            let mut items: Vec<_> = manifest.in_category(*category).cloned().collect();
+            items.sort_by_key(|item| std::cmp::Reverse(item.updated_at));
+            for item in items {
+                rows.push_back(item);
+            }
+
+            group.set_visible(!rows.is_empty());
+        }
+
+        self.is_empty = manifest.items().is_empty();
+    }
+
+    fn item(&self, id: u64) -> Option<&InstalledItem> {
+        self.groups
+            .iter()
+            .flat_map(|(_, rows)| rows.iter())
+            .map(|row| &row.item)
+            .find(|item| item.id == id)
+    }
+
+    /// Removes an item in the background, since every file is checked first.
+    fn remove(&self, id: u64, force: bool, sender: &ComponentSender<Self>) {
+        sender.spawn_oneshot_command(move || {
+            let installer = match Installer::from_env() {
+                Ok(installer) => installer,
+                Err(e) => return LibraryCommand::Removed(Err(e.to_string())),
+            };
+
+            match installer.uninstall(id, force) {
+                Err(UninstallError::ModifiedSinceInstall(files)) => {
+                    LibraryCommand::Modified(id, files)
+                }
+                removed => LibraryCommand::Removed(removed.map_err(|e| e.to_string())),
+            }
+        });
+    }
+}
+
+/// Asks whether to remove an item whose files were changed after all.
+fn ask_to_force_remove(
+    root: &gtk::Stack,
+    id: u64,
+    files: &[PathBuf],
+    sender: &ComponentSender<LibraryPage>,
+) {
+    let dialog = gtk::MessageDialog::builder()
+        .modal(true)
+        .text(&gettext("Remove Changed Files?"))
+        .secondary_text(
+            &gettext("{} files were changed since they were installed, like {}. Removing it deletes them anyway.")
+                .replacen("{}", &files.len().to_string(), 1)
+                .replacen("{}", &files[0].display().to_string(), 1),
+        )
+        .build();
+    if let Some(window) = root
+        .root()
+        .and_then(|root| root.downcast::<gtk::Window>().ok())
+    {
+        dialog.set_transient_for(Some(&window));
+    }
+    dialog.add_button(&gettext("_Cancel"), gtk::ResponseType::Cancel);
+    dialog
+        .add_button(&gettext("_Remove"), gtk::ResponseType::Accept)
+        .add_css_class("destructive-action");
+
+    let sender = sender.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == gtk::ResponseType::Accept {
+            sender.input(LibraryMsg::ForceRemove(id));
+        }
+        dialog.close();
+    });
+    dialog.present();
+}
+
+/// One installed item.
+#[derive(Debug)]
+pub struct LibraryRow {
+    item: InstalledItem,
+}
+
+#[relm4::factory(pub)]
+impl FactoryComponent for LibraryRow {
+    type Init = InstalledItem;
+    type Input = ();
+    type Output = LibraryMsg;
+    type CommandOutput = ();
+    type ParentInput = LibraryMsg;
+    type ParentWidget = adw::PreferencesGroup;
+
+    view! {
+        adw::ExpanderRow {
+            set_title: &self.item.name(),
+            set_subtitle: &self.installed_on(),
+
+            add_action = &gtk::Box {
+                set_valign: gtk::Align::Center,
+                set_spacing: 6,
+
+                gtk::Button {
+                    set_icon_name: "folder-open-symbolic",
+                    set_tooltip_text: Some(&gettext("Open Folder")),
+                    add_css_class: "flat",
+                    connect_clicked[sender, id = self.item.id] => move |_| {
+                        sender.output(LibraryMsg::OpenFolder(id));
+                    },
+                },
+                gtk::Button {
+                    set_icon_name: "view-refresh-symbolic",
+                    set_tooltip_text: Some(&gettext("Reinstall")),
+                    add_css_class: "flat",
+                    connect_clicked[sender, id = self.item.id] => move |_| {
+                        sender.output(LibraryMsg::Reinstall(id));
+                    },
+                },
+                gtk::Button {
+                    set_icon_name: "user-trash-symbolic",
+                    set_tooltip_text: Some(&gettext("Remove")),
+                    add_css_class: "flat",
+                    connect_clicked[sender, id = self.item.id] => move |_| {
+                        sender.output(LibraryMsg::Remove(id));
+                    },
+                },
+            },
+
+            add_row = &adw::ActionRow {
+                set_title: &gettext("Destination"),
+                set_subtitle: &self.item.destination.display().to_string(),
+            },
+            add_row = &adw::ActionRow {
+                set_title: &gettext("Source"),
+                set_subtitle: self.item.source_url.as_str(),
+            },
+        }
+    }
+
+    fn output_to_parent_input(output: Self::Output) -> Option<LibraryMsg> {
+        Some(output)
+    }
+
+    fn init_model(item: Self::Init, _index: &DynamicIndex, _sender: FactorySender<Self>) -> Self {
+        Self { item }
+    }
+}
+
+impl LibraryRow {
+    /// Like "Installed 03/14/23".
+    fn installed_on(&self) -> String {
+        let seconds = self
+            .item
+            .installed_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let date = glib::DateTime::from_unix_local(seconds)
+            .and_then(|date| date.format("%x"))
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+
+        gettext("Installed {}").replacen("{}", &date, 1)
+    }
+}
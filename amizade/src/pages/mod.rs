@@ -0,0 +1,3 @@
+pub mod confirm;
+pub mod library;
+pub mod progress;
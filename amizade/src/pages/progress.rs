@@ -0,0 +1,192 @@
+use ocs_custodian::handler::DownloadProgress;
+use ocs_custodian::progress::{InstallEvent, InstallStage};
+use relm4::{adw, gtk, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+
+use gettextrs::gettext;
+use gtk::glib;
+use gtk::prelude::{BoxExt, ButtonExt, OrientableExt, WidgetExt};
+use std::time::Instant;
+
+/// Shows how an install is going, and lets it be cancelled.
+pub struct ProgressPage {
+    name: String,
+    stage: InstallStage,
+    download: Option<DownloadProgress>,
+    /// When the first bytes arrived, to work out the rate.
+    started: Option<Instant>,
+    cancelling: bool,
+}
+
+#[derive(Debug)]
+pub enum ProgressMsg {
+    /// A new install of something called this is starting.
+    Start(String),
+    Event(InstallEvent),
+    Cancel,
+}
+
+#[derive(Debug)]
+pub enum ProgressOutput {
+    Cancel,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for ProgressPage {
+    type Init = ();
+    type Input = ProgressMsg;
+    type Output = ProgressOutput;
+    type Widgets = ProgressPageWidgets;
+
+    view! {
+        adw::Clamp {
+            set_valign: gtk::Align::Center,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 12,
+                set_margin_all: 24,
+
+                gtk::Label {
+                    add_css_class: "title-1",
+                    set_wrap: true,
+                    #[watch]
+                    set_label: &gettext("Installing {}").replacen("{}", &model.name, 1),
+                },
+
+                gtk::Label {
+                    add_css_class: "title-4",
+                    #[watch]
+                    set_label: &model.stage_label(),
+                },
+
+                #[name = "bar"]
+                gtk::ProgressBar {},
+
+                gtk::Label {
+                    add_css_class: "dim-label",
+                    add_css_class: "numeric",
+                    #[watch]
+                    set_label: &model.download_label(),
+                },
+
+                gtk::Button {
+                    set_halign: gtk::Align::Center,
+                    set_margin_top: 12,
+                    set_label: &gettext("_Cancel"),
+                    set_use_underline: true,
+                    add_css_class: "pill",
+                    #[watch]
+                    set_sensitive: model.can_cancel() && !model.cancelling,
+                    connect_clicked => ProgressMsg::Cancel,
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            name: String::new(),
+            stage: InstallStage::Downloading,
+            download: None,
+            started: None,
+            cancelling: false,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            ProgressMsg::Start(name) => {
+                self.name = name;
+                self.stage = InstallStage::Downloading;
+                self.download = None;
+                self.started = None;
+                self.cancelling = false;
+            }
+            ProgressMsg::Event(InstallEvent::Stage(stage)) => self.stage = stage,
+            ProgressMsg::Event(InstallEvent::Download(download)) => {
+                if download.downloaded > 0 && self.started.is_none() {
+                    self.started = Some(Instant::now());
+                }
+                self.download = Some(download);
+            }
+            ProgressMsg::Cancel => {
+                self.cancelling = true;
+                sender.output(ProgressOutput::Cancel).unwrap();
+            }
+        }
+    }
+
+    fn post_view() {
+        match model.fraction() {
+            Some(fraction) => bar.set_fraction(fraction),
+            None => bar.pulse(),
+        }
+    }
+}
+
+impl ProgressPage {
+    fn stage_label(&self) -> String {
+        if self.cancelling && self.can_cancel() {
+            return gettext("Cancelling…");
+        }
+
+        match self.stage {
+            InstallStage::Downloading => gettext("Downloading…"),
+            InstallStage::Extracting => gettext("Unpacking…"),
+            InstallStage::Placing => gettext("Putting it in place…"),
+            InstallStage::Finishing => gettext("Finishing up…"),
+        }
+    }
+
+    /// Like "1.2 MB of 3.4 MB (500 kB/s)".
+    fn download_label(&self) -> String {
+        let Some(download) = self.download else {
+            return String::new();
+        };
+
+        let mut label = match download.total {
+            Some(total) => gettext("{} of {}")
+                .replacen("{}", &glib::format_size(download.downloaded), 1)
+                .replacen("{}", &glib::format_size(total), 1),
+            None => glib::format_size(download.downloaded).to_string(),
+        };
+
+        let elapsed = self.started.map(|started| started.elapsed().as_secs_f64());
+        if let Some(elapsed) = elapsed.filter(|elapsed| *elapsed > 0.5) {
+            let rate = (download.downloaded as f64 / elapsed) as u64;
+            label.push_str(&format!(" ({}/s)", glib::format_size(rate)));
+        }
+
+        label
+    }
+
+    /// How far along the whole install is, if we can tell.
+    fn fraction(&self) -> Option<f64> {
+        match self.stage {
+            InstallStage::Downloading => {
+                let download = self.download?;
+                let total = download.total.filter(|total| *total > 0)?;
+                Some(download.downloaded as f64 / total as f64)
+            }
+            // unpacking doesn't say how far along it is
+            InstallStage::Extracting => None,
+            InstallStage::Placing | InstallStage::Finishing => Some(1.0),
+        }
+    }
+
+    /// Once things start going into place, the install has to finish.
+    fn can_cancel(&self) -> bool {
+        matches!(
+            self.stage,
+            InstallStage::Downloading | InstallStage::Extracting
+        )
+    }
+}
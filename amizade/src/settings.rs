@@ -0,0 +1,121 @@
+//! The parts of Amizade's GSettings that change how things get installed.
+use ocs_custodian::config::ConfigError;
+use ocs_custodian::installer::{ConflictPolicy, Installer};
+use ocs_custodian::plan::InstallPlan;
+use ocs_custodian::types::install_type::InstallType;
+use relm4::gtk;
+
+use gtk::gio;
+use gtk::prelude::{SettingsExt, SettingsExtManual};
+use std::collections::HashMap;
+
+use crate::config::APP_ID;
+
+/// When to ask before installing a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Confirm {
+    Always,
+    /// Only for links that download from hosts that aren't trusted.
+    Untrusted,
+    Never,
+}
+
+impl Confirm {
+    pub(crate) const ALL: [Self; 3] = [Self::Always, Self::Untrusted, Self::Never];
+
+    /// The value of the `confirm-installs` key.
+    pub(crate) fn key(self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::Untrusted => "untrusted",
+            Self::Never => "never",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|confirm| confirm.key() == key)
+            .unwrap_or(Self::Always)
+    }
+}
+
+/// A copy of the settings, so that they can be sent to a background thread.
+#[derive(Debug, Clone)]
+pub(crate) struct InstallSettings {
+    pub conflict_policy: ConflictPolicy,
+    /// Destination overrides, by the main alias of an install type.
+    pub destinations: HashMap<String, String>,
+    pub auto_apply: bool,
+    pub confirm: Confirm,
+    pub trusted_hosts: Vec<String>,
+}
+
+impl InstallSettings {
+    pub(crate) fn load() -> Self {
+        let settings = gio::Settings::new(APP_ID);
+
+        Self {
+            conflict_policy: ConflictPolicy::try_from(settings.string("conflict-policy").as_str())
+                .unwrap_or_default(),
+            destinations: settings.get("destinations"),
+            auto_apply: settings.boolean("auto-apply"),
+            confirm: Confirm::from_key(&settings.string("confirm-installs")),
+            trusted_hosts: settings
+                .strv("trusted-hosts")
+                .iter()
+                .map(|host| host.to_string())
+                .collect(),
+        }
+    }
+
+    /// An installer for the current user's links of `install_type`. These
+    /// settings beat the ones in their config file.
+    ///
+    /// Only the destination for `install_type` is applied. Checking one means
+    /// writing to it, and a folder that went away shouldn't stop everything
+    /// else from installing. The preferences check them as they're picked.
+    pub(crate) fn installer(&self, install_type: InstallType) -> Result<Installer, ConfigError> {
+        let mut installer = Installer::from_env()?;
+        let destination = self
+            .destinations
+            .iter()
+            .find(|(alias, _)| InstallType::from_alias(alias).ok() == Some(install_type));
+        if let Some((alias, destination)) = destination {
+            installer
+                .config
+                .set_type_override(alias, destination, &installer.resolver)?;
+        }
+        installer.conflict_policy = self.conflict_policy;
+
+        Ok(installer)
+    }
+
+    /// Whether to ask before going ahead with a plan. Plans that would do
+    /// something about an installed item are always asked about.
+    pub(crate) fn needs_confirmation(&self, plan: &InstallPlan) -> bool {
+        if !plan.conflicts.is_empty() {
+            return true;
+        }
+
+        match self.confirm {
+            Confirm::Always => true,
+            Confirm::Untrusted => !plan
+                .link
+                .download_url
+                .host_str()
+                .is_some_and(|host| self.is_trusted(host)),
+            Confirm::Never => false,
+        }
+    }
+
+    /// Trusting a host trusts its subdomains too.
+    fn is_trusted(&self, host: &str) -> bool {
+        self.trusted_hosts.iter().any(|trusted| {
+            host == trusted
+                || host
+                    .strip_suffix(trusted.as_str())
+                    .is_some_and(|sub| sub.ends_with('.'))
+        })
+    }
+}
@@ -0,0 +1,599 @@
+//! Unpacks the archives that most Pling items come in.
+//!
+//! Formats are recognized by their magic bytes instead of by extension, since
+//! file names from strangers can't be trusted. For the same reason, every entry
+//! is checked before it's written: nothing may escape the destination through
+//! `..`, absolute paths or symlinks, and [`ExtractLimits`] keep archive bombs
+//! from filling up the disk.
+use crate::progress::Cancellation;
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// The kinds of files that we know how to unpack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarXz,
+    TarBz2,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Figures out the format from the first bytes of a file.
+    /// Returns `None` if it's not an archive we know about.
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::TarGz)
+        } else if bytes.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Self::TarXz)
+        } else if bytes.starts_with(b"BZh") {
+            Some(Self::TarBz2)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::TarZst)
+        } else if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+            Some(Self::Zip)
+        } else if bytes.get(257..262) == Some(b"ustar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// Figures out the format of the file at `path`.
+    pub fn detect(path: &Path) -> io::Result<Option<Self>> {
+        let mut magic = Vec::with_capacity(512);
+        File::open(path)?.take(512).read_to_end(&mut magic)?;
+
+        Ok(Self::from_magic(&magic))
+    }
+}
+
+/// How much an archive is allowed to unpack to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    /// The most bytes that all entries can add up to.
+    pub max_total_size: u64,
+    /// The most entries (files, directories and links) an archive can have.
+    pub max_entries: u64,
+    /// How many times bigger than the archive its contents can be.
+    pub max_ratio: u64,
+}
+
+impl Default for ExtractLimits {
+    /// Roomy enough for huge icon themes, but not for bombs.
+    fn default() -> Self {
+        Self {
+            max_total_size: 4 * 1024 * 1024 * 1024,
+            max_entries: 250_000,
+            max_ratio: 250,
+        }
+    }
+}
+
+/// Represents a failure to unpack an archive.
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("This file isn't an archive that we know how to unpack.")]
+    NotAnArchive,
+    #[error("The archive has an entry that would end up outside of where it's unpacked: `{}`", .0.display())]
+    UnsafePath(PathBuf),
+    #[error("The archive has a link that points outside of where it's unpacked: `{}` -> `{}`", entry.display(), target.display())]
+    UnsafeLink { entry: PathBuf, target: PathBuf },
+    #[error("The archive has an entry that isn't a file, directory or link: `{}`", .0.display())]
+    UnsupportedEntry(PathBuf),
+    #[error("The archive unpacks to more than {0} bytes.")]
+    TooBig(u64),
+    #[error("The archive has more than {0} entries.")]
+    TooManyEntries(u64),
+    #[error("The archive unpacks to more than {0} times its own size.")]
+    RatioTooHigh(u64),
+    #[error("The archive is damaged: {0}")]
+    Corrupt(String),
+    #[error("Unpacking the archive was cancelled.")]
+    Cancelled,
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+impl From<zip::result::ZipError> for ArchiveError {
+    fn from(error: zip::result::ZipError) -> Self {
+        match error {
+            zip::result::ZipError::Io(e) => Self::IoError(e),
+            other => Self::Corrupt(other.to_string()),
+        }
+    }
+}
+
+/// Something inside of an archive, as found by [`list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArchiveEntry {
+    /// Where it would go, relative to where the archive is unpacked.
+    pub path: PathBuf,
+    /// How many bytes it has. Always 0 for directories and links.
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Unpacks the archive at `archive` into the `destination` directory.
+///
+/// Returns the top-level items that were created, in the order they appeared.
+pub fn extract(
+    archive: &Path,
+    destination: &Path,
+    limits: &ExtractLimits,
+) -> Result<Vec<PathBuf>, ArchiveError> {
+    extract_cancellable(archive, destination, limits, &Cancellation::new())
+}
+
+/// Like [`extract`], but stops with [`ArchiveError::Cancelled`] once `cancel`
+/// is cancelled. Whatever was unpacked by then is left for the caller to clean
+/// up, just like after any other error.
+pub fn extract_cancellable(
+    archive: &Path,
+    destination: &Path,
+    limits: &ExtractLimits,
+    cancel: &Cancellation,
+) -> Result<Vec<PathBuf>, ArchiveError> {
+    let format = ArchiveFormat::detect(archive)?.ok_or(ArchiveError::NotAnArchive)?;
+    let archive_size = fs::metadata(archive)?.len();
+
+    fs::create_dir_all(destination)?;
+
+    let mut extractor = Extractor::new(Some(destination), limits, cancel, archive_size);
+    extractor.run(format, io::BufReader::new(File::open(archive)?))?;
+
+    Ok(extractor
+        .top_level
+        .iter()
+        .map(|item| destination.join(item))
+        .collect())
+}
+
+/// Lists what's in the archive at `archive`, without writing anything. The
+/// same checks as [`extract`] are made along the way.
+pub fn list(archive: &Path, limits: &ExtractLimits) -> Result<Vec<ArchiveEntry>, ArchiveError> {
+    let format = ArchiveFormat::detect(archive)?.ok_or(ArchiveError::NotAnArchive)?;
+    let archive_size = fs::metadata(archive)?.len();
+
+    let cancel = Cancellation::new();
+    let mut extractor = Extractor::new(None, limits, &cancel, archive_size);
+    extractor.run(format, io::BufReader::new(File::open(archive)?))?;
+
+    Ok(extractor.listed)
+}
+
+/// How many links in a row we'll follow, like Linux's `MAXSYMLINKS`.
+const MAX_LINK_HOPS: usize = 40;
+
+/// Keeps track of an extraction in progress.
+struct Extractor<'a> {
+    /// Where things are written to, or `None` to only look.
+    root: Option<&'a Path>,
+    limits: &'a ExtractLimits,
+    cancel: &'a Cancellation,
+    /// The smaller of the total size and ratio limits.
+    max_size: u64,
+    written: u64,
+    entries: u64,
+    /// The first parts of every entry's path, in order.
+    top_level: Vec<PathBuf>,
+    listed: Vec<ArchiveEntry>,
+    /// Every directory so far, relative to the root.
+    dirs: HashSet<PathBuf>,
+    /// Every symlink so far and where it points, relative to the root.
+    links: HashMap<PathBuf, PathBuf>,
+}
+
+/// What an archive entry turns into.
+enum EntryKind {
+    File { mode: Option<u32> },
+    Directory,
+    Symlink(PathBuf),
+    HardLink(PathBuf),
+}
+
+impl<'a> Extractor<'a> {
+    fn new(
+        root: Option<&'a Path>,
+        limits: &'a ExtractLimits,
+        cancel: &'a Cancellation,
+        archive_size: u64,
+    ) -> Self {
+        Self {
+            root,
+            limits,
+            cancel,
+            max_size: limits
+                .max_total_size
+                .min(archive_size.max(1).saturating_mul(limits.max_ratio)),
+            written: 0,
+            entries: 0,
+            top_level: Vec::new(),
+            listed: Vec::new(),
+            dirs: HashSet::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    /// Goes through the whole archive.
+    fn run(
+        &mut self,
+        format: ArchiveFormat,
+        reader: impl io::BufRead + io::Seek,
+    ) -> Result<(), ArchiveError> {
+        match format {
+            ArchiveFormat::Tar => self.tar(reader),
+            ArchiveFormat::TarGz => self.tar(flate2::read::MultiGzDecoder::new(reader)),
+            ArchiveFormat::TarXz => self.tar(xz2::read::XzDecoder::new(reader)),
+            ArchiveFormat::TarBz2 => self.tar(bzip2::read::BzDecoder::new(reader)),
+            ArchiveFormat::TarZst => self.tar(zstd::stream::read::Decoder::with_buffer(reader)?),
+            ArchiveFormat::Zip => self.zip(reader),
+        }?;
+
+        // links can only be followed once they're all there
+        for (entry, link) in &self.links {
+            if self.escapes(entry, link) {
+                return Err(ArchiveError::UnsafeLink {
+                    entry: entry.clone(),
+                    target: link.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Unpacks a tarball from an already-decompressed stream.
+    fn tar(&mut self, reader: impl Read) -> Result<(), ArchiveError> {
+        let mut archive = tar::Archive::new(reader);
+
+        for entry in archive.entries().map_err(corrupt)? {
+            let mut entry = entry.map_err(corrupt)?;
+            let path = entry.path().map_err(corrupt)?.into_owned();
+            let header = entry.header();
+
+            let kind = match header.entry_type() {
+                tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File {
+                    mode: header.mode().ok(),
+                },
+                tar::EntryType::Directory => EntryKind::Directory,
+                tar::EntryType::Symlink | tar::EntryType::Link => {
+                    let target = entry
+                        .link_name()
+                        .map_err(corrupt)?
+                        .ok_or_else(|| ArchiveError::Corrupt("a link has no target".into()))?
+                        .into_owned();
+
+                    if header.entry_type() == tar::EntryType::Symlink {
+                        EntryKind::Symlink(target)
+                    } else {
+                        EntryKind::HardLink(target)
+                    }
+                }
+                // pax and GNU headers describe the next entry, and the tar
+                // crate already applied them for us
+                tar::EntryType::XGlobalHeader
+                | tar::EntryType::XHeader
+                | tar::EntryType::GNULongName
+                | tar::EntryType::GNULongLink => continue,
+                _ => return Err(ArchiveError::UnsupportedEntry(path)),
+            };
+
+            self.entry(&path, kind, &mut entry)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unpacks a zip file.
+    fn zip(&mut self, reader: impl Read + io::Seek) -> Result<(), ArchiveError> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+
+        // don't even start on a zip that says it's too big
+        if archive.len() as u64 > self.limits.max_entries {
+            return Err(ArchiveError::TooManyEntries(self.limits.max_entries));
+        }
+
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let path = PathBuf::from(entry.name());
+            let mode = entry.unix_mode();
+
+            let kind = if mode.is_some_and(|mode| mode & 0o170000 == 0o120000) {
+                let mut target = String::new();
+                (&mut entry).take(4096).read_to_string(&mut target)?;
+                EntryKind::Symlink(PathBuf::from(target))
+            } else if entry.is_dir() {
+                EntryKind::Directory
+            } else {
+                EntryKind::File { mode }
+            };
+
+            self.entry(&path, kind, &mut entry)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks and writes one entry.
+    fn entry(
+        &mut self,
+        path: &Path,
+        kind: EntryKind,
+        contents: &mut impl Read,
+    ) -> Result<(), ArchiveError> {
+        if self.cancel.is_cancelled() {
+            return Err(ArchiveError::Cancelled);
+        }
+
+        self.entries += 1;
+        if self.entries > self.limits.max_entries {
+            return Err(ArchiveError::TooManyEntries(self.limits.max_entries));
+        }
+
+        let Some(relative) = normalize(path)? else {
+            // entries like `./` are just the root itself
+            return Ok(());
+        };
+
+        // writing through a link we made earlier could land anywhere
+        self.check_no_links_above(&relative)?;
+
+        if let Some(first) = relative.components().next() {
+            let first = PathBuf::from(first.as_os_str());
+            if !self.top_level.contains(&first) {
+                self.top_level.push(first);
+            }
+        }
+
+        match &kind {
+            EntryKind::Symlink(link) => {
+                if link.is_absolute() || !stays_inside(&relative, link) {
+                    return Err(ArchiveError::UnsafeLink {
+                        entry: path.to_path_buf(),
+                        target: link.clone(),
+                    });
+                }
+            }
+            EntryKind::HardLink(link) => {
+                // hard link targets are relative to the archive's root
+                let Some(source) = normalize(link)? else {
+                    return Err(ArchiveError::UnsafeLink {
+                        entry: path.to_path_buf(),
+                        target: link.clone(),
+                    });
+                };
+                self.check_no_links_above(&source)?;
+
+                // hard linking a symlink copies it, which would move where it
+                // points to
+                if self.links.contains_key(&source) {
+                    return Err(ArchiveError::UnsafeLink {
+                        entry: path.to_path_buf(),
+                        target: link.clone(),
+                    });
+                }
+                if self.dirs.contains(&source) {
+                    return Err(ArchiveError::UnsupportedEntry(path.to_path_buf()));
+                }
+            }
+            EntryKind::File { .. } | EntryKind::Directory => (),
+        }
+
+        self.dirs
+            .extend(relative.ancestors().skip(1).map(Path::to_path_buf));
+        self.links.remove(&relative);
+        match &kind {
+            EntryKind::Directory => {
+                self.dirs.insert(relative.clone());
+            }
+            EntryKind::Symlink(link) => {
+                self.links.insert(relative.clone(), link.clone());
+            }
+            EntryKind::File { .. } | EntryKind::HardLink(_) => (),
+        }
+
+        let before = self.written;
+        match self.root {
+            Some(root) => self.write(&root.join(&relative), kind, contents)?,
+            None => {
+                if let EntryKind::File { .. } = kind {
+                    self.copy(contents, &mut io::sink())?;
+                }
+            }
+        }
+
+        self.listed.push(ArchiveEntry {
+            size: self.written - before,
+            is_dir: self.dirs.contains(&relative),
+            path: relative,
+        });
+
+        Ok(())
+    }
+
+    /// Puts an entry that was already checked on the disk.
+    fn write(
+        &mut self,
+        target: &Path,
+        kind: EntryKind,
+        contents: &mut impl Read,
+    ) -> Result<(), ArchiveError> {
+        let root = self.root.expect("only called when writing");
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // a later entry replaces a link instead of writing through it
+        if target.symlink_metadata().is_ok_and(|m| !m.is_dir()) {
+            fs::remove_file(target)?;
+        }
+
+        match kind {
+            EntryKind::Directory => fs::create_dir_all(target)?,
+            EntryKind::File { mode } => {
+                let mut file = File::create(target)?;
+                self.copy(contents, &mut file)?;
+
+                // keep executable bits for scripts, but nothing fancier
+                if let Some(mode) = mode {
+                    fs::set_permissions(target, fs::Permissions::from_mode(mode & 0o755))?;
+                }
+            }
+            EntryKind::Symlink(link) => std::os::unix::fs::symlink(link, target)?,
+            EntryKind::HardLink(link) => {
+                let source = root.join(normalize(&link)?.expect("checked already"));
+                fs::hard_link(source, target)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies an entry's contents, keeping an eye on the size limits.
+    fn copy(
+        &mut self,
+        reader: &mut impl Read,
+        writer: &mut impl Write,
+    ) -> Result<(), ArchiveError> {
+        let mut buffer = vec![0; 64 * 1024];
+
+        loop {
+            if self.cancel.is_cancelled() {
+                return Err(ArchiveError::Cancelled);
+            }
+
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(corrupt(e)),
+            };
+
+            self.written += read as u64;
+            if self.written > self.limits.max_total_size {
+                return Err(ArchiveError::TooBig(self.limits.max_total_size));
+            }
+            if self.written > self.max_size {
+                return Err(ArchiveError::RatioTooHigh(self.limits.max_ratio));
+            }
+
+            writer.write_all(&buffer[..read])?;
+        }
+    }
+
+    /// Makes sure that none of the directories leading to `relative` are
+    /// symlinks.
+    fn check_no_links_above(&self, relative: &Path) -> Result<(), ArchiveError> {
+        match relative
+            .ancestors()
+            .skip(1)
+            .any(|dir| self.links.contains_key(dir))
+        {
+            true => Err(ArchiveError::UnsafePath(relative.to_path_buf())),
+            false => Ok(()),
+        }
+    }
+
+    /// Follows the symlink at `entry` like the OS would, through every other
+    /// link in the archive. Each link staying inside on its own isn't enough:
+    /// `a/b -> ..` and `a/c -> b/..` are both fine alone, but `a/c` ends up
+    /// above the root.
+    ///
+    /// Links that go around in circles count as escaping, too.
+    fn escapes(&self, entry: &Path, link: &Path) -> bool {
+        let mut current: Vec<&OsStr> = match entry.parent() {
+            Some(parent) => parent.iter().collect(),
+            None => Vec::new(),
+        };
+        // what's left to walk through, backwards
+        let mut todo: Vec<Component> = link.components().rev().collect();
+        let mut hops = 0;
+
+        while let Some(component) = todo.pop() {
+            match component {
+                Component::Normal(part) => {
+                    current.push(part);
+
+                    let here: PathBuf = current.iter().collect();
+                    if let Some(next) = self.links.get(&here) {
+                        hops += 1;
+                        if hops > MAX_LINK_HOPS {
+                            return true;
+                        }
+
+                        current.pop();
+                        todo.extend(next.components().rev());
+                    }
+                }
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    if current.pop().is_none() {
+                        return true;
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => return true,
+            }
+        }
+
+        false
+    }
+}
+
+/// Turns an entry's path into a plain relative one, refusing anything that
+/// could climb out of the destination. Returns `None` for the root itself.
+fn normalize(path: &Path) -> Result<Option<PathBuf>, ArchiveError> {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => (),
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(ArchiveError::UnsafePath(path.to_path_buf()))
+            }
+        }
+    }
+
+    if normalized.as_os_str().is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(normalized))
+    }
+}
+
+/// Checks if a relative symlink at `entry` pointing to `link` stays inside of
+/// the destination.
+fn stays_inside(entry: &Path, link: &Path) -> bool {
+    // how deep the link's directory is, from the destination's root
+    let mut depth = entry.components().count() as i64 - 1;
+
+    for component in link.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => (),
+            Component::ParentDir => depth -= 1,
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+
+        if depth < 0 {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Most errors from inside of an archive mean it's broken.
+fn corrupt(error: io::Error) -> ArchiveError {
+    ArchiveError::Corrupt(error.to_string())
+}
@@ -0,0 +1,239 @@
+//! User configuration for ocs-custodian.
+//!
+//! Lives at `$XDG_CONFIG_HOME/amizade/config.toml`. It lets you change where
+//! things get installed, and what happens when they're already there:
+//!
+//! ```toml
+//! [install]
+//! conflict_policy = "backup"
+//!
+//! # any alias of an install type works as a key
+//! [destinations]
+//! fonts = "/mnt/shared/fonts"
+//! icons = "$HOME/shared/icons"
+//!
+//! # or change a whole category at once
+//! [categories]
+//! wm_themes = "/mnt/shared/wm-themes"
+//! ```
+//!
+//! Install type overrides beat category overrides. Every override is checked
+//! when the file is loaded, so a typo or unwritable path is reported right away
+//! instead of halfway through an install.
+use crate::installer::ConflictPolicy;
+use crate::paths::{PathError, PathResolver, APP_DIR_NAME};
+use crate::types::install_type::{InstallCategory, InstallStrategy, InstallType};
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The name of the config file inside of `$XDG_CONFIG_HOME/amizade`.
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Represents a problem with the user's config file.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Couldn't read the config file at `{path}`: {source}")]
+    Unreadable { path: PathBuf, source: io::Error },
+    #[error("The config file isn't valid TOML: {0}")]
+    InvalidToml(#[from] toml::de::Error),
+    #[error("The config file has an unknown section or key: `{0}`")]
+    UnknownKey(String),
+    #[error("`{0}` in the config file should be a table.")]
+    NotATable(String),
+    #[error("`{0}` in the config file should be a path, like \"$HOME/fonts\".")]
+    NotAPath(String),
+    #[error("`{0}` in the config file isn't a known install type.")]
+    UnknownInstallType(String),
+    #[error("`{0}` in the config file isn't a known install category.")]
+    UnknownCategory(String),
+    #[error("`{0}` isn't a conflict policy. Try one of: skip, overwrite, keep-both or backup.")]
+    UnknownConflictPolicy(String),
+    #[error("The destination for `{key}` must be an absolute path, but it's `{}`.", path.display())]
+    RelativePath { key: String, path: PathBuf },
+    #[error("The destination for `{key}` isn't writable: `{}`", path.display())]
+    NotWritable { key: String, path: PathBuf },
+    #[error(transparent)]
+    PathError(#[from] PathError),
+}
+
+/// The user's settings. Use [`Config::default`] for no overrides at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    type_overrides: HashMap<InstallType, PathBuf>,
+    category_overrides: HashMap<InstallCategory, PathBuf>,
+    conflict_policy: ConflictPolicy,
+}
+
+impl Config {
+    /// Where the config file lives.
+    pub fn path(resolver: &PathResolver) -> Result<PathBuf, PathError> {
+        Ok(resolver
+            .xdg_config_home()?
+            .join(APP_DIR_NAME)
+            .join(CONFIG_FILE_NAME))
+    }
+
+    /// Loads the user's config file. If there isn't one, nothing is overridden.
+    pub fn load(resolver: &PathResolver) -> Result<Self, ConfigError> {
+        let path = Self::path(resolver)?;
+
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::from_toml(&text, resolver),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(ConfigError::Unreadable { path, source }),
+        }
+    }
+
+    /// Reads a config from TOML text, checking every override.
+    pub fn from_toml(text: &str, resolver: &PathResolver) -> Result<Self, ConfigError> {
+        let table: toml::Table = text.parse()?;
+        let mut config = Self::default();
+
+        for (section, values) in table {
+            if !matches!(section.as_str(), "destinations" | "categories" | "install") {
+                return Err(ConfigError::UnknownKey(section));
+            }
+            let values = match values {
+                toml::Value::Table(values) => values,
+                _ => return Err(ConfigError::NotATable(section)),
+            };
+
+            for (key, value) in values {
+                let full_key = || format!("{section}.{key}");
+
+                // what the value should be depends on the key
+                match (section.as_str(), key.as_str(), value) {
+                    ("destinations", _, toml::Value::String(value)) => {
+                        config.set_type_override(&key, &value, resolver)?
+                    }
+                    ("categories", _, toml::Value::String(value)) => {
+                        config.set_category_override(&key, &value, resolver)?
+                    }
+                    ("destinations" | "categories", _, _) => {
+                        return Err(ConfigError::NotAPath(full_key()))
+                    }
+                    ("install", "conflict_policy", value) => {
+                        let policy = match &value {
+                            toml::Value::String(value) => ConflictPolicy::try_from(value.as_str()),
+                            _ => Err(value.to_string()),
+                        };
+                        config.conflict_policy =
+                            policy.map_err(ConfigError::UnknownConflictPolicy)?
+                    }
+                    _ => return Err(ConfigError::UnknownKey(full_key())),
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Sends everything of the install type with the given alias to `destination`.
+    pub fn set_type_override(
+        &mut self,
+        alias: &str,
+        destination: &str,
+        resolver: &PathResolver,
+    ) -> Result<(), ConfigError> {
+        let install_type = InstallType::from_alias(alias)
+            .map_err(|_| ConfigError::UnknownInstallType(alias.into()))?;
+        let destination = checked_destination(alias, destination, resolver)?;
+
+        self.type_overrides.insert(install_type, destination);
+        Ok(())
+    }
+
+    /// Sends everything in the given category to `destination`.
+    pub fn set_category_override(
+        &mut self,
+        category: &str,
+        destination: &str,
+        resolver: &PathResolver,
+    ) -> Result<(), ConfigError> {
+        let category = InstallCategory::try_from(category)
+            .map_err(|_| ConfigError::UnknownCategory(category.into()))?;
+        let destination = checked_destination(&category.to_string(), destination, resolver)?;
+
+        self.category_overrides.insert(category, destination);
+        Ok(())
+    }
+
+    /// What to do with things that are already installed, unless an install
+    /// asks for something else.
+    pub fn conflict_policy(&self) -> ConflictPolicy {
+        self.conflict_policy
+    }
+
+    /// Changes the default conflict policy.
+    pub fn set_conflict_policy(&mut self, policy: ConflictPolicy) {
+        self.conflict_policy = policy;
+    }
+
+    /// Where the given install type should go, taking overrides into account.
+    pub fn destination(
+        &self,
+        install_type: &InstallType,
+        resolver: &PathResolver,
+    ) -> Result<PathBuf, PathError> {
+        if let Some(path) = self.type_overrides.get(install_type) {
+            return Ok(path.clone());
+        }
+        if let Some(path) = self.category_overrides.get(&install_type.category()) {
+            return Ok(path.clone());
+        }
+
+        resolver.resolve(&install_type.get_install_path())
+    }
+}
+
+/// Resolves an override and makes sure that we could actually write there.
+fn checked_destination(
+    key: &str,
+    destination: &str,
+    resolver: &PathResolver,
+) -> Result<PathBuf, ConfigError> {
+    let path = resolver.resolve(destination)?;
+
+    if !path.is_absolute() {
+        return Err(ConfigError::RelativePath {
+            key: key.into(),
+            path,
+        });
+    }
+    if !is_writable(&path) {
+        return Err(ConfigError::NotWritable {
+            key: key.into(),
+            path,
+        });
+    }
+
+    Ok(path)
+}
+
+/// Checks if we could write to `path`, creating it if needed.
+///
+/// Missing directories are fine as long as the closest one that does exist is
+/// writable. We check by actually creating a file, since permission bits don't
+/// tell the whole story (read-only mounts, ACLs, ...).
+fn is_writable(path: &Path) -> bool {
+    let Some(existing) = path.ancestors().find(|p| p.symlink_metadata().is_ok()) else {
+        return false;
+    };
+    if !existing.is_dir() {
+        return false;
+    }
+
+    let probe = existing.join(format!(".amizade-write-test-{}", std::process::id()));
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => fs::remove_file(probe).is_ok(),
+        Err(_) => false,
+    }
+}
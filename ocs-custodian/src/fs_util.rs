@@ -0,0 +1,81 @@
+//! Small filesystem helpers that std doesn't have.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Moves a file or directory. If renaming doesn't work (like across
+/// filesystems), it's copied over and then removed.
+pub(crate) fn move_item(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    copy_item(from, to)?;
+    remove_item(from)
+}
+
+/// Copies a file, symlink or whole directory.
+pub(crate) fn copy_item(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = from.symlink_metadata()?;
+
+    if metadata.is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)
+    } else if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_item(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Lists every file and symlink under `path`, including `path` itself if it's
+/// not a directory.
+pub(crate) fn files_in(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let metadata = path.symlink_metadata()?;
+
+    if !metadata.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        files.extend(files_in(&entry?.path())?);
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// Removes a file, symlink or whole directory.
+pub(crate) fn remove_item(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Makes a new, empty directory in `parent` that nobody else is using.
+pub(crate) fn unique_dir(parent: &Path, prefix: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(parent)?;
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+
+    for attempt in 0.. {
+        let dir = parent.join(format!("{prefix}-{}-{nanos}-{attempt}", std::process::id()));
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    unreachable!("we'll find a free name eventually")
+}
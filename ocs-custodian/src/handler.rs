@@ -1,18 +1,274 @@
+use crate::progress::Cancellation;
 use crate::types::ParsedOcsUrl;
 
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use url::Url;
+
+/// The version of the JSON made by [`ParsedOcsUrl::to_json`]. This goes up
+/// whenever a change could break someone reading it.
+#[cfg(feature = "serde")]
+pub const JSON_SCHEMA_VERSION: u64 = 1;
+
+/// The name downloads get when neither the link nor its URL has a better one.
+const FALLBACK_FILENAME: &str = "download";
+
+/// Represents a failure while downloading the file behind a link.
+#[derive(Error, Debug)]
+pub enum DownloadError {
+    #[error("The server responded with HTTP {status} for `{url}`.")]
+    HttpStatus { url: Url, status: u16 },
+    #[error("Couldn't download `{url}`: {message}")]
+    Transport { url: Url, message: String },
+    #[error("The download is bigger than {0} bytes.")]
+    TooBig(u64),
+    #[error("The download ended early. Expected {expected} bytes, but got {received}.")]
+    Incomplete { expected: u64, received: u64 },
+    #[error("`{0}` can't be used as a file name.")]
+    BadFilename(String),
+    #[error("The download was cancelled.")]
+    Cancelled,
+    #[error("Couldn't save the download: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Represents a failure to read a link back from JSON.
+#[cfg(feature = "serde")]
+#[derive(Error, Debug)]
+pub enum JsonError {
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
+    #[error(
+        "This JSON uses schema version {0:?}, but only version {JSON_SCHEMA_VERSION} is supported."
+    )]
+    UnsupportedSchemaVersion(Option<u64>),
+}
+
+/// Knobs for downloading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadOptions {
+    /// How long to wait for the server to pick up.
+    pub connect_timeout: Duration,
+    /// How long to wait for the next chunk of data before giving up.
+    pub read_timeout: Duration,
+    /// The most bytes to download, if there's a limit.
+    pub max_size: Option<u64>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(15),
+            read_timeout: Duration::from_secs(30),
+            max_size: None,
+        }
+    }
+}
+
+/// How far along a download is. `total` is only known if the server told us.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
 impl ParsedOcsUrl {
-    /// todo: get download link or something
-    pub fn download() {
-        todo!();
+    /// The name to save the download as. That's the link's `filename` if it has
+    /// one, or the last part of the download URL otherwise.
+    pub fn download_filename(&self) -> Result<String, DownloadError> {
+        let name = match &self.filename {
+            Some(filename) => filename.clone(),
+            None => self
+                .download_url
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| {
+                    urlencoding::decode(segment)
+                        .map(|s| s.into_owned())
+                        .unwrap_or_else(|_| segment.to_owned())
+                })
+                .unwrap_or_else(|| FALLBACK_FILENAME.to_owned()),
+        };
+
+        // no sneaking out of the staging directory!
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\0']) {
+            return Err(DownloadError::BadFilename(name));
+        }
+
+        Ok(name)
     }
 
-    /// todo: return all info as json or whatever
-    pub fn to_json() {
-        todo!("probably gonna use serde..?");
+    /// Downloads the file behind the link into `staging_dir`, returning where
+    /// it was saved.
+    ///
+    /// The file is written as it arrives, and `progress` is called after every
+    /// chunk. Nothing is left behind if the download fails.
+    pub fn download(
+        &self,
+        staging_dir: &Path,
+        options: &DownloadOptions,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<PathBuf, DownloadError> {
+        self.download_cancellable(staging_dir, options, &Cancellation::new(), progress)
     }
 
-    /// todo: get screenshots if available
-    pub fn get_screenshots() {
-        todo!("no idea how to get these from Pling right now :(");
+    /// Like [`ParsedOcsUrl::download`], but stops with
+    /// [`DownloadError::Cancelled`] once `cancel` is cancelled.
+    pub fn download_cancellable(
+        &self,
+        staging_dir: &Path,
+        options: &DownloadOptions,
+        cancel: &Cancellation,
+        mut progress: impl FnMut(DownloadProgress),
+    ) -> Result<PathBuf, DownloadError> {
+        let destination = staging_dir.join(self.download_filename()?);
+        let partial = staging_dir.join(format!(
+            ".{}.part",
+            destination
+                .file_name()
+                .expect("download filenames are checked")
+                .to_string_lossy()
+        ));
+
+        let result = File::create(&partial)
+            .map_err(DownloadError::from)
+            .and_then(|mut file| {
+                self.stream_to(&mut file, options, cancel, &mut progress)?;
+                file.sync_all()?;
+                Ok(())
+            })
+            .and_then(|_| fs::rename(&partial, &destination).map_err(DownloadError::from));
+
+        if result.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+
+        result.map(|_| destination)
+    }
+
+    /// Streams the download into `writer`.
+    fn stream_to(
+        &self,
+        writer: &mut impl Write,
+        options: &DownloadOptions,
+        cancel: &Cancellation,
+        progress: &mut impl FnMut(DownloadProgress),
+    ) -> Result<(), DownloadError> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(options.connect_timeout)
+            .timeout_read(options.read_timeout)
+            .build();
+
+        let response = match agent.get(self.download_url.as_str()).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(status, _)) => {
+                return Err(DownloadError::HttpStatus {
+                    url: self.download_url.clone(),
+                    status,
+                })
+            }
+            Err(ureq::Error::Transport(transport)) => {
+                return Err(DownloadError::Transport {
+                    url: self.download_url.clone(),
+                    message: transport.to_string(),
+                })
+            }
+        };
+
+        let total = response
+            .header("Content-Length")
+            .and_then(|length| length.parse::<u64>().ok());
+
+        // no point in starting on something we'd give up on anyway
+        if let (Some(total), Some(max_size)) = (total, options.max_size) {
+            if total > max_size {
+                return Err(DownloadError::TooBig(max_size));
+            }
+        }
+
+        let mut reader = response.into_reader();
+        let mut buffer = vec![0; 64 * 1024];
+        let mut downloaded = 0;
+
+        progress(DownloadProgress { downloaded, total });
+
+        loop {
+            if cancel.is_cancelled() {
+                return Err(DownloadError::Cancelled);
+            }
+
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    return Err(DownloadError::Transport {
+                        url: self.download_url.clone(),
+                        message: e.to_string(),
+                    })
+                }
+            };
+
+            downloaded += read as u64;
+            if let Some(max_size) = options.max_size.filter(|&max| downloaded > max) {
+                return Err(DownloadError::TooBig(max_size));
+            }
+            writer.write_all(&buffer[..read])?;
+            progress(DownloadProgress { downloaded, total });
+        }
+
+        match total {
+            Some(expected) if expected != downloaded => Err(DownloadError::Incomplete {
+                expected,
+                received: downloaded,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the link as JSON, following the schema in
+    /// `schema/parsed-ocs-url.schema.json`:
+    ///
+    /// ```json
+    /// {
+    ///   "schema_version": 1,
+    ///   "ocs_url": "ocs://install?url=https%3A%2F%2Fexample.com%2FNordic.tar.xz&type=gtk3_themes",
+    ///   "scheme": "ocs",
+    ///   "command": "install",
+    ///   "download_url": "https://example.com/Nordic.tar.xz",
+    ///   "install_type": { "category": "Styling", "kind": "GTK3Themes" },
+    ///   "install_type_alias": "gtk3_themes",
+    ///   "filename": null
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        let mut json = serde_json::to_value(self)?;
+        json["schema_version"] = JSON_SCHEMA_VERSION.into();
+
+        serde_json::to_string(&json)
+    }
+
+    /// Reads a link back from the JSON made by [`ParsedOcsUrl::to_json`].
+    ///
+    /// The link is parsed again from `ocs_url`, and every other field has to
+    /// agree with it.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, JsonError> {
+        let mut json: serde_json::Value = serde_json::from_str(json)?;
+
+        match json.get("schema_version").and_then(|v| v.as_u64()) {
+            Some(JSON_SCHEMA_VERSION) => (),
+            other => return Err(JsonError::UnsupportedSchemaVersion(other)),
+        }
+        if let Some(json) = json.as_object_mut() {
+            json.remove("schema_version");
+        }
+
+        Ok(serde_json::from_value(json)?)
     }
 }
@@ -1,4 +1,672 @@
-// TODO: make this do something
-pub fn install() {
-    // todo!
+//! Puts the things behind links onto the disk.
+//!
+//! An [`Installer`] downloads a link's file, works out where it goes from its
+//! install type (and the user's [`Config`]), then moves it into place. Archives
+//! behind `install` links are unpacked first, and their contents go in instead.
+//!
+//! Putting things in place either fully works or changes nothing, so a failed
+//! install never leaves half a theme behind or loses the version it replaced.
+//! Successful installs are recorded in the [`Manifest`], which is also what
+//! [`Installer::uninstall`] goes by.
+//!
+//! Frontends that want to show more than a download bar can use
+//! [`Installer::install_with`], which reports every stage and can be cancelled
+//! (see [`crate::progress`]). To show what an install will do first, make an
+//! [`InstallPlan`] with [`Installer::plan`], then hand it to
+//! [`Installer::install_plan`].
+use crate::archive::{extract_cancellable, ArchiveError, ArchiveFormat, ExtractLimits};
+use crate::config::{Config, ConfigError};
+use crate::fs_util::{files_in, unique_dir};
+use crate::handler::{DownloadError, DownloadOptions, DownloadProgress};
+use crate::legacy::{find_legacy_item, LegacyError};
+use crate::manifest::{hash_file, Manifest, ManifestError};
+use crate::paths::{PathError, PathResolver};
+use crate::plan::{InstallPlan, PostInstallAction};
+use crate::progress::{Cancellation, InstallEvent, InstallStage};
+use crate::transaction::Transaction;
+use crate::types::install_type::{InstallStrategy, InstallType, InstallTypeError};
+use crate::types::{Command, ParsedOcsUrl};
+
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Represents a failure while installing a link.
+#[derive(Error, Debug)]
+pub enum InstallError {
+    #[error(transparent)]
+    DownloadError(#[from] DownloadError),
+    #[error(transparent)]
+    PathError(#[from] PathError),
+    #[error(transparent)]
+    LegacyError(#[from] LegacyError),
+    #[error(transparent)]
+    ArchiveError(#[from] ArchiveError),
+    #[error(transparent)]
+    InstallTypeError(#[from] InstallTypeError),
+    #[error(transparent)]
+    ManifestError(#[from] ManifestError),
+    #[error("Couldn't write to `{}`: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error(
+        "`{}` installs into a system folder, which needs root. Give it a destination in the config file to install it somewhere else.",
+        .0.aliases()[0]
+    )]
+    NeedsRoot(InstallType),
+    #[error("`{}` changed after the install was planned.", .0.display())]
+    ChangedSincePlanned(PathBuf),
+    #[error("`{}` isn't in the staging area, so it can't be installed.", .0.display())]
+    NotStaged(PathBuf),
+    #[error("This would go into `{}` now, which isn't what the plan said. Check the link again.", .0.display())]
+    DestinationChanged(PathBuf),
+    #[error("The install was cancelled.")]
+    Cancelled,
+}
+
+/// What to do when something with the same name is already installed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum ConflictPolicy {
+    /// Leave the old one alone, and don't install the new one.
+    Skip,
+    /// Replace the old one.
+    Overwrite,
+    /// Install the new one under a name like `Papirus-2`.
+    KeepBoth,
+    /// Rename the old one to something like `Papirus.bak`, then install.
+    /// Nothing is lost this way, so it's the default.
+    #[default]
+    Backup,
+}
+
+impl ConflictPolicy {
+    pub const ALL: [Self; 4] = [Self::Skip, Self::Overwrite, Self::KeepBoth, Self::Backup];
+
+    /// Works out what this policy would do about something already being at
+    /// `target`, without changing anything. Returns `None` if nothing's there.
+    pub fn resolve(self, target: &Path, is_dir: bool) -> Option<Conflict> {
+        target.symlink_metadata().ok()?;
+
+        let moved_to = match self {
+            Self::Skip | Self::Overwrite => None,
+            Self::KeepBoth => Some(free_name(target, is_dir, "-")),
+            Self::Backup => {
+                let backup = format!("{}.bak", item_name(target));
+                Some(free_name(&target.with_file_name(backup), false, "."))
+            }
+        };
+
+        Some(Conflict {
+            existing: target.to_path_buf(),
+            policy: self,
+            moved_to,
+        })
+    }
+
+    /// Works out what this policy would do about an old copy of an item in a
+    /// legacy location like `~/.themes`, when the new one is going to
+    /// `target`. Unless it's skipped, the new one still goes there.
+    pub fn resolve_legacy(self, legacy: &Path, target: &Path) -> Conflict {
+        let moved_to = match self {
+            Self::Skip | Self::Overwrite => None,
+            Self::KeepBoth => Some(target.to_path_buf()),
+            Self::Backup => {
+                let backup = format!("{}.bak", item_name(legacy));
+                Some(free_name(&legacy.with_file_name(backup), false, "."))
+            }
+        };
+
+        Conflict {
+            existing: legacy.to_path_buf(),
+            policy: self,
+            moved_to,
+        }
+    }
+}
+
+impl Display for ConflictPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let text = match self {
+            Self::Skip => "skip",
+            Self::Overwrite => "overwrite",
+            Self::KeepBoth => "keep-both",
+            Self::Backup => "backup",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+impl TryFrom<&str> for ConflictPolicy {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.to_string() == value.replace('_', "-"))
+            .ok_or_else(|| value.to_owned())
+    }
+}
+
+/// Something that was in the way during an install, and what was done about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflict {
+    /// What was already installed. That can be an old copy in a legacy
+    /// location, see [`ConflictPolicy::resolve_legacy`].
+    pub existing: PathBuf,
+    pub policy: ConflictPolicy,
+    /// Where the new item went, for [`ConflictPolicy::KeepBoth`], or where the
+    /// old one went, for [`ConflictPolicy::Backup`].
+    pub moved_to: Option<PathBuf>,
+}
+
+impl Conflict {
+    /// Where the new item goes, or `None` if it's skipped. Only for conflicts
+    /// from [`ConflictPolicy::resolve`].
+    pub fn new_location(&self) -> Option<PathBuf> {
+        match self.policy {
+            ConflictPolicy::Skip => None,
+            ConflictPolicy::Overwrite | ConflictPolicy::Backup => Some(self.existing.clone()),
+            ConflictPolicy::KeepBoth => self.moved_to.clone(),
+        }
+    }
+}
+
+/// What an install did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstallReport {
+    pub install_type: InstallType,
+    /// The directory that everything went into.
+    pub destination: PathBuf,
+    /// The things placed right inside of `destination`.
+    pub items: Vec<PathBuf>,
+    /// Every file that was written, including those inside of `items`.
+    pub files: Vec<PathBuf>,
+    /// Anything that was already there, and what the policy did with it.
+    pub conflicts: Vec<Conflict>,
+    /// Things that went wrong after everything was in place, like the font
+    /// cache not updating. The install still worked.
+    pub warnings: Vec<String>,
+}
+
+/// Represents a failure while removing an installed item.
+#[derive(Error, Debug)]
+pub enum UninstallError {
+    #[error(transparent)]
+    ManifestError(#[from] ManifestError),
+    #[error("Nothing with the id {0} is installed.")]
+    NotInstalled(u64),
+    #[error("{} file(s) changed since they were installed, like `{}`.", .0.len(), .0[0].display())]
+    ModifiedSinceInstall(Vec<PathBuf>),
+    #[error("Couldn't remove `{}`: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// What an uninstall did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UninstallReport {
+    pub id: u64,
+    /// The files that were deleted.
+    pub removed: Vec<PathBuf>,
+    /// Recorded files that were already gone.
+    pub missing: Vec<PathBuf>,
+    /// Directories that were left because something else is in them.
+    pub kept: Vec<PathBuf>,
+}
+
+/// Installs links.
+#[derive(Debug, Clone)]
+pub struct Installer {
+    pub resolver: PathResolver,
+    pub config: Config,
+    pub download_options: DownloadOptions,
+    pub extract_limits: ExtractLimits,
+    pub conflict_policy: ConflictPolicy,
+}
+
+impl Installer {
+    /// Makes an installer with the given environment and settings.
+    pub fn new(resolver: PathResolver, config: Config) -> Self {
+        Self {
+            resolver,
+            conflict_policy: config.conflict_policy(),
+            config,
+            download_options: DownloadOptions::default(),
+            extract_limits: ExtractLimits::default(),
+        }
+    }
+
+    /// Makes an installer for the current user, reading their config file.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let resolver = PathResolver::from_env();
+        let config = Config::load(&resolver)?;
+
+        Ok(Self::new(resolver, config))
+    }
+
+    /// Where the given link will be installed to.
+    pub fn destination(&self, link: &ParsedOcsUrl) -> Result<PathBuf, PathError> {
+        self.config.destination(&link.install_type, &self.resolver)
+    }
+
+    /// Like [`Installer::destination`], but refuses install types that would
+    /// go into a system folder, before anything is downloaded.
+    pub(crate) fn checked_destination(&self, link: &ParsedOcsUrl) -> Result<PathBuf, InstallError> {
+        let destination = self.destination(link)?;
+
+        // an override means the user picked somewhere they can write to
+        let default = Path::new(&link.install_type.get_install_path()).to_path_buf();
+        if link.install_type.is_system_wide() && destination == default {
+            return Err(InstallError::NeedsRoot(link.install_type));
+        }
+
+        Ok(destination)
+    }
+
+    /// Downloads the link's file and puts it where its install type says.
+    ///
+    /// `progress` hears about the download as it happens.
+    pub fn install(
+        &self,
+        link: &ParsedOcsUrl,
+        mut progress: impl FnMut(DownloadProgress),
+    ) -> Result<InstallReport, InstallError> {
+        self.install_with(link, &Cancellation::new(), |event| {
+            if let InstallEvent::Download(download) = event {
+                progress(download);
+            }
+        })
+    }
+
+    /// Like [`Installer::install`], but `events` hears about each
+    /// [`InstallStage`] as well as the download.
+    ///
+    /// Once `cancel` is cancelled, the install stops with
+    /// [`InstallError::Cancelled`] and everything it downloaded or unpacked is
+    /// removed. That works up until things start going into place: from then
+    /// on, the install finishes so nothing is left half-done.
+    pub fn install_with(
+        &self,
+        link: &ParsedOcsUrl,
+        cancel: &Cancellation,
+        mut events: impl FnMut(InstallEvent),
+    ) -> Result<InstallReport, InstallError> {
+        let destination = self.checked_destination(link)?;
+
+        let staging_root = self.resolver.app_data()?.join("staging");
+        let staging = unique_dir(&staging_root, "install").map_err(|source| InstallError::Io {
+            path: staging_root,
+            source,
+        })?;
+
+        events(InstallEvent::Stage(InstallStage::Downloading));
+        let result = link
+            .download_cancellable(
+                &staging,
+                &self.capped_download_options(),
+                cancel,
+                |download| events(InstallEvent::Download(download)),
+            )
+            .map_err(InstallError::from)
+            .and_then(|downloaded| {
+                let policy = self.conflict_policy;
+                self.install_from(link, downloaded, &destination, policy, cancel, &mut events)
+            });
+
+        // the staging directory is ours, so it always goes away
+        let _ = fs::remove_dir_all(&staging);
+
+        result.map_err(stopped_early)
+    }
+
+    /// Installs what [`Installer::plan`] downloaded, with the plan's conflict
+    /// policy, without downloading it again.
+    ///
+    /// The plan isn't taken at its word: its download has to be in the
+    /// staging area and match its hash, and the link has to still go where
+    /// the plan says it does. Either way, the download is gone afterwards: a
+    /// plan can only be installed once.
+    ///
+    /// `cancel` and `events` work like in [`Installer::install_with`], but
+    /// there's nothing left to download.
+    pub fn install_plan(
+        &self,
+        plan: &InstallPlan,
+        cancel: &Cancellation,
+        mut events: impl FnMut(InstallEvent),
+    ) -> Result<InstallReport, InstallError> {
+        let result = self.check_plan(plan).and_then(|(staged, destination)| {
+            self.install_from(
+                &plan.link,
+                staged,
+                &destination,
+                plan.conflict_policy,
+                cancel,
+                &mut events,
+            )
+        });
+
+        plan.discard(&self.resolver);
+
+        result.map_err(stopped_early)
+    }
+
+    /// Makes sure a plan can be installed, returning its download and where
+    /// it goes.
+    fn check_plan(&self, plan: &InstallPlan) -> Result<(PathBuf, PathBuf), InstallError> {
+        let destination = self.checked_destination(&plan.link)?;
+        if destination != plan.destination {
+            return Err(InstallError::DestinationChanged(destination));
+        }
+
+        plan.staging_dir(&self.resolver)?;
+        let (sha256, _) = hash_file(&plan.staged).map_err(|source| InstallError::Io {
+            path: plan.staged.clone(),
+            source,
+        })?;
+        if sha256 != plan.sha256 {
+            return Err(InstallError::ChangedSincePlanned(plan.staged.clone()));
+        }
+
+        Ok((plan.staged.clone(), destination))
+    }
+
+    /// The download options, capped so that nothing is downloaded that would
+    /// be too big to unpack anyway.
+    pub(crate) fn capped_download_options(&self) -> DownloadOptions {
+        let limit = self.extract_limits.max_total_size;
+        DownloadOptions {
+            max_size: Some(
+                self.download_options
+                    .max_size
+                    .map_or(limit, |max| max.min(limit)),
+            ),
+            ..self.download_options.clone()
+        }
+    }
+
+    /// Does the actual work of [`Installer::install`], once the file is
+    /// downloaded. Archives are unpacked next to `downloaded`, so it has to be
+    /// in a staging directory of its own.
+    fn install_from(
+        &self,
+        link: &ParsedOcsUrl,
+        downloaded: PathBuf,
+        destination: &Path,
+        policy: ConflictPolicy,
+        cancel: &Cancellation,
+        events: &mut impl FnMut(InstallEvent),
+    ) -> Result<InstallReport, InstallError> {
+        let staging = downloaded
+            .parent()
+            .expect("downloads are in a staging directory")
+            .to_path_buf();
+        let staged = self.unpack(link, downloaded, &staging, cancel, events)?;
+
+        for item in &staged {
+            link.install_type.check_layout(item)?;
+        }
+
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| InstallError::Io { path, source }
+        };
+
+        if cancel.is_cancelled() {
+            return Err(InstallError::Cancelled);
+        }
+        events(InstallEvent::Stage(InstallStage::Placing));
+
+        // everything goes in with renames, and comes back out if one fails
+        let mut transaction = Transaction::begin(destination).map_err(io_error(destination))?;
+        let mut placements = Vec::new();
+        for item in staged {
+            let name = item_name(&item);
+            // an old copy in `~/.themes` and friends is in the way too
+            let legacy = find_legacy_item(&link.install_type, &self.resolver, destination, &name)?;
+
+            let target = destination.join(name);
+            let staged = transaction.stage(&item).map_err(io_error(&target))?;
+            placements.push((staged, target, legacy));
+        }
+
+        let mut report = InstallReport {
+            install_type: link.install_type,
+            destination: destination.to_path_buf(),
+            items: Vec::new(),
+            files: Vec::new(),
+            conflicts: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        for (staged, target, legacy) in placements {
+            let Some(target) = self
+                .resolve_conflict(&mut transaction, policy, target, &staged, &mut report)
+                .map_err(io_error(destination))?
+            else {
+                continue;
+            };
+            if let Some(legacy) = legacy {
+                let conflict = policy.resolve_legacy(&legacy, &target);
+                match (conflict.policy, &conflict.moved_to) {
+                    (ConflictPolicy::Skip, _) => {
+                        report.conflicts.push(conflict);
+                        continue;
+                    }
+                    (ConflictPolicy::Overwrite, _) => transaction.remove(&legacy),
+                    (ConflictPolicy::Backup, Some(backup)) => transaction.rename(&legacy, backup),
+                    _ => Ok(()),
+                }
+                .map_err(io_error(&legacy))?;
+                report.conflicts.push(conflict);
+            }
+
+            transaction
+                .place(&staged, &target)
+                .map_err(io_error(&target))?;
+
+            report
+                .files
+                .extend(files_in(&target).map_err(io_error(&target))?);
+            report.items.push(target);
+        }
+
+        if report.items.is_empty() {
+            // everything was skipped, so there's nothing to remember
+            transaction.commit().map_err(io_error(destination))?;
+            return Ok(report);
+        }
+
+        events(InstallEvent::Stage(InstallStage::Finishing));
+
+        transaction.commit().map_err(io_error(destination))?;
+
+        // the files are only hashed once they're really in place
+        let _lock = Manifest::lock(&self.resolver)?;
+        let mut manifest = Manifest::load(&self.resolver)?;
+        manifest.record(link, &report)?;
+        manifest.save()?;
+
+        // these are nice to have, so they can't fail the install
+        for action in PostInstallAction::for_install_type(link.install_type) {
+            if let Err(e) = action.run(destination) {
+                report.warnings.push(e.to_string());
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Applies the conflict policy if something is already at `target`.
+    /// Returns where `staged` should go, or `None` if it should be skipped.
+    fn resolve_conflict(
+        &self,
+        transaction: &mut Transaction,
+        policy: ConflictPolicy,
+        target: PathBuf,
+        staged: &Path,
+        report: &mut InstallReport,
+    ) -> io::Result<Option<PathBuf>> {
+        let Some(conflict) = policy.resolve(&target, staged.is_dir()) else {
+            return Ok(Some(target));
+        };
+
+        if let (ConflictPolicy::Backup, Some(backup)) = (conflict.policy, &conflict.moved_to) {
+            transaction.rename(&target, backup)?;
+        }
+
+        let placed_at = conflict.new_location();
+        report.conflicts.push(conflict);
+
+        Ok(placed_at)
+    }
+
+    /// Removes the files an install wrote, then forgets about it.
+    ///
+    /// Only files in the [`Manifest`] are deleted, and directories only go away
+    /// once they're empty. Files that changed since they were installed are
+    /// left alone, unless `force` is set.
+    pub fn uninstall(&self, id: u64, force: bool) -> Result<UninstallReport, UninstallError> {
+        let _lock = Manifest::lock(&self.resolver)?;
+        let mut manifest = Manifest::load(&self.resolver)?;
+        let item = manifest.get(id).ok_or(UninstallError::NotInstalled(id))?;
+
+        let mut report = UninstallReport {
+            id,
+            removed: Vec::new(),
+            missing: Vec::new(),
+            kept: Vec::new(),
+        };
+
+        let mut present = Vec::new();
+        let mut modified = Vec::new();
+        for file in &item.files {
+            match hash_file(&file.path) {
+                Ok((sha256, _)) if sha256 == file.sha256 => present.push(&file.path),
+                Ok(_) => {
+                    modified.push(file.path.clone());
+                    present.push(&file.path);
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    report.missing.push(file.path.clone())
+                }
+                // it's there, but we can't tell what's in it
+                Err(_) => {
+                    modified.push(file.path.clone());
+                    present.push(&file.path);
+                }
+            }
+        }
+        if !modified.is_empty() && !force {
+            return Err(UninstallError::ModifiedSinceInstall(modified));
+        }
+
+        for path in present {
+            fs::remove_file(path).map_err(|source| UninstallError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            report.removed.push(path.clone());
+        }
+
+        for item in &item.items {
+            let is_dir = item.symlink_metadata().is_ok_and(|m| m.is_dir());
+            if is_dir && !prune_empty_dirs(item) {
+                report.kept.push(item.clone());
+            }
+        }
+
+        manifest.remove(id);
+        manifest.save()?;
+
+        Ok(report)
+    }
+
+    /// Unpacks `downloaded` if it's an archive behind an `install` link,
+    /// returning the items to put in place.
+    fn unpack(
+        &self,
+        link: &ParsedOcsUrl,
+        downloaded: PathBuf,
+        staging: &Path,
+        cancel: &Cancellation,
+        events: &mut impl FnMut(InstallEvent),
+    ) -> Result<Vec<PathBuf>, InstallError> {
+        if link.command != Command::Install {
+            return Ok(vec![downloaded]);
+        }
+
+        let format = ArchiveFormat::detect(&downloaded).map_err(|source| InstallError::Io {
+            path: downloaded.clone(),
+            source,
+        })?;
+        if format.is_none() {
+            return Ok(vec![downloaded]);
+        }
+
+        events(InstallEvent::Stage(InstallStage::Extracting));
+        let items = extract_cancellable(
+            &downloaded,
+            &staging.join("extracted"),
+            &self.extract_limits,
+            cancel,
+        )?;
+        Ok(items)
+    }
+}
+
+/// Turns the ways a download or unpack can be cancelled into one.
+fn stopped_early(error: InstallError) -> InstallError {
+    match error {
+        InstallError::DownloadError(DownloadError::Cancelled)
+        | InstallError::ArchiveError(ArchiveError::Cancelled) => InstallError::Cancelled,
+        e => e,
+    }
+}
+
+/// Finds a name next to `path` that isn't taken yet, by adding a number like
+/// `Papirus-2` or `cursor-2.png`. Directories don't have extensions.
+fn free_name(path: &Path, is_dir: bool, separator: &str) -> PathBuf {
+    if path.symlink_metadata().is_err() {
+        return path.to_path_buf();
+    }
+
+    let name = item_name(path);
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !is_dir && !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (name.as_str(), String::new()),
+    };
+
+    (2..)
+        .map(|n| path.with_file_name(format!("{stem}{separator}{n}{extension}")))
+        .find(|candidate| candidate.symlink_metadata().is_err())
+        .expect("there's always a free name")
+}
+
+/// Removes `dir` and the directories under it, as long as they're empty.
+/// Returns whether `dir` itself went away.
+fn prune_empty_dirs(dir: &Path) -> bool {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                prune_empty_dirs(&entry.path());
+            }
+        }
+    }
+
+    fs::remove_dir(dir).is_ok()
+}
+
+/// The file name of something we staged. These always have one.
+fn item_name(item: &Path) -> String {
+    item.file_name()
+        .expect("staged items have names")
+        .to_string_lossy()
+        .into_owned()
 }
@@ -0,0 +1,133 @@
+//! Deals with things installed to the old, pre-XDG locations.
+//!
+//! Fonts, icons and themes used to go straight into `~/.fonts`, `~/.icons` and
+//! `~/.themes`. Most desktops still read those, so we shouldn't install a
+//! second copy of something that's already there. Users can also ask us to
+//! move everything over to the XDG locations.
+use crate::fs_util::move_item;
+use crate::paths::{PathError, PathResolver};
+use crate::types::install_type::InstallStrategy;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Represents a failure when checking or migrating legacy locations.
+#[derive(Error, Debug)]
+pub enum LegacyError {
+    #[error(transparent)]
+    PathError(#[from] PathError),
+    #[error("Couldn't move `{from}` to `{to}`: {source}")]
+    MoveFailed {
+        from: PathBuf,
+        to: PathBuf,
+        source: io::Error,
+    },
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+/// What happened when migrating a legacy location.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Items that were moved, as `(from, to)`.
+    pub moved: Vec<(PathBuf, PathBuf)>,
+    /// Items that were left alone, since something with the same name is
+    /// already in the XDG location.
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Looks for an item called `name` in the legacy location of `install_type`,
+/// for an install into `destination`.
+///
+/// Returns `None` if there's no legacy location, nothing's there, or the legacy
+/// location is just a link to the current one or `destination` itself.
+pub fn find_legacy_item(
+    install_type: &impl InstallStrategy,
+    resolver: &PathResolver,
+    destination: &Path,
+    name: &str,
+) -> Result<Option<PathBuf>, LegacyError> {
+    let Some((legacy, _)) = locations(install_type, resolver)? else {
+        return Ok(None);
+    };
+
+    // the config can point an install type right at it
+    if same_dir(&legacy, destination) {
+        return Ok(None);
+    }
+
+    let item = legacy.join(name);
+    if item.symlink_metadata().is_ok() {
+        Ok(Some(item))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Moves everything in the legacy location of `install_type` into its XDG
+/// location. Items that would clobber something are skipped.
+pub fn migrate(
+    install_type: &impl InstallStrategy,
+    resolver: &PathResolver,
+) -> Result<MigrationReport, LegacyError> {
+    let mut report = MigrationReport::default();
+
+    let Some((legacy, current)) = locations(install_type, resolver)? else {
+        return Ok(report);
+    };
+
+    fs::create_dir_all(&current)?;
+
+    for entry in fs::read_dir(&legacy)? {
+        let from = entry?.path();
+        let to = current.join(from.file_name().expect("read_dir entries have names"));
+
+        if to.symlink_metadata().is_ok() {
+            report.skipped.push(from);
+            continue;
+        }
+
+        move_item(&from, &to).map_err(|source| LegacyError::MoveFailed {
+            from: from.clone(),
+            to: to.clone(),
+            source,
+        })?;
+        report.moved.push((from, to));
+    }
+
+    Ok(report)
+}
+
+/// Gets the `(legacy, current)` locations for an install type, but only if the
+/// legacy one exists and is actually a different directory.
+fn locations(
+    install_type: &impl InstallStrategy,
+    resolver: &PathResolver,
+) -> Result<Option<(PathBuf, PathBuf)>, LegacyError> {
+    let Some(legacy) = install_type.get_legacy_install_path() else {
+        return Ok(None);
+    };
+    let legacy = resolver.resolve(&legacy)?;
+    let current = resolver.install_path(install_type)?;
+
+    if !legacy.is_dir() {
+        return Ok(None);
+    }
+
+    // people often link `~/.themes` to `~/.local/share/themes` themselves
+    if same_dir(&legacy, &current) {
+        return Ok(None);
+    }
+
+    Ok(Some((legacy, current)))
+}
+
+/// Checks if two paths are the same directory, following links.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
@@ -1,7 +1,17 @@
+pub mod archive;
+pub mod config;
+mod fs_util;
 pub mod handler;
 pub mod installer;
+pub mod legacy;
+pub mod manifest;
+pub mod ocs_api;
 pub mod parser;
+pub mod paths;
+pub mod plan;
+pub mod progress;
 mod tests;
-mod types;
+mod transaction;
+pub mod types;
 
 pub use types::ParsedOcsUrl;
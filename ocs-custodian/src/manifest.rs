@@ -0,0 +1,402 @@
+//! Remembers everything that was installed.
+//!
+//! The [`Manifest`] lives in `$XDG_DATA_HOME/amizade/installed.json`, and has
+//! an [`InstalledItem`] for each install: the link it came from, where it went
+//! and a hash of every file that was written, so that it can be removed or
+//! checked later.
+use crate::installer::InstallReport;
+use crate::parser::check_url;
+use crate::paths::{PathError, PathResolver};
+use crate::types::install_type::{InstallCategory, InstallType};
+use crate::types::ParsedOcsUrl;
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use url::Url;
+
+/// The version of the manifest's format. Bump this when it changes.
+pub const MANIFEST_VERSION: u64 = 1;
+
+/// Represents a failure to read or write the manifest.
+#[derive(Error, Debug)]
+pub enum ManifestError {
+    #[error(transparent)]
+    PathError(#[from] PathError),
+    #[error("Couldn't read the installed items from `{}`: {source}", path.display())]
+    Unreadable { path: PathBuf, source: io::Error },
+    #[error("Couldn't save the installed items to `{}`: {source}", path.display())]
+    Unwritable { path: PathBuf, source: io::Error },
+    #[error("Couldn't lock the installed items at `{}`: {source}", path.display())]
+    Unlockable { path: PathBuf, source: io::Error },
+    #[error("Couldn't hash `{}`: {source}", path.display())]
+    Unhashable { path: PathBuf, source: io::Error },
+    #[error("The installed items in `{}` are damaged: {message}", path.display())]
+    Corrupt { path: PathBuf, message: String },
+    #[error("The installed items were saved by a newer version (format {0}).")]
+    UnsupportedVersion(u64),
+}
+
+/// A file that an install wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstalledFile {
+    pub path: PathBuf,
+    /// The SHA-256 of the file's contents, or of a symlink's target.
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Everything we know about one install.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstalledItem {
+    /// Stays the same when the item is reinstalled.
+    pub id: u64,
+    pub link: ParsedOcsUrl,
+    pub install_type: InstallType,
+    pub destination: PathBuf,
+    /// The things placed right inside of `destination`.
+    pub items: Vec<PathBuf>,
+    pub files: Vec<InstalledFile>,
+    pub installed_at: SystemTime,
+    pub updated_at: SystemTime,
+    /// Where the file was downloaded from.
+    pub source_url: Url,
+}
+
+impl InstalledItem {
+    /// A name for people, taken from the first thing that was installed.
+    pub fn name(&self) -> String {
+        self.items
+            .first()
+            .and_then(|item| item.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.id.to_string())
+    }
+
+    /// Checks if `path` is one of the files or items this install wrote.
+    pub fn owns(&self, path: &Path) -> bool {
+        self.items.iter().any(|item| item == path) || self.files.iter().any(|f| f.path == path)
+    }
+}
+
+/// Keeps other installs and uninstalls from touching the manifest until it's
+/// dropped. Take it before loading a manifest that's going to be saved.
+#[derive(Debug)]
+pub struct ManifestLock {
+    _file: File,
+}
+
+/// The list of installed items, as saved on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    path: PathBuf,
+    items: Vec<InstalledItem>,
+}
+
+impl Manifest {
+    /// Where the manifest is kept.
+    pub fn path(resolver: &PathResolver) -> Result<PathBuf, PathError> {
+        Ok(resolver.app_data()?.join("installed.json"))
+    }
+
+    /// Waits until nothing else is changing the user's manifest. The lock is
+    /// advisory, and kept in `installed.json.lock` next to it.
+    pub fn lock(resolver: &PathResolver) -> Result<ManifestLock, ManifestError> {
+        let path = Self::path(resolver)?.with_extension("json.lock");
+        let unlockable = |source| ManifestError::Unlockable {
+            path: path.clone(),
+            source,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(unlockable)?;
+        }
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(unlockable)?;
+        file.lock().map_err(unlockable)?;
+
+        Ok(ManifestLock { _file: file })
+    }
+
+    /// Loads the user's manifest. Nothing being installed yet isn't an error.
+    pub fn load(resolver: &PathResolver) -> Result<Self, ManifestError> {
+        Self::open(&Self::path(resolver)?)
+    }
+
+    /// Loads the manifest at `path`, or starts an empty one there.
+    pub fn open(path: &Path) -> Result<Self, ManifestError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    path: path.to_path_buf(),
+                    items: Vec::new(),
+                })
+            }
+            Err(source) => {
+                return Err(ManifestError::Unreadable {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        let corrupt = |message: &str| ManifestError::Corrupt {
+            path: path.to_path_buf(),
+            message: message.to_owned(),
+        };
+
+        let json: Value = serde_json::from_str(&text).map_err(|e| corrupt(&e.to_string()))?;
+        match json["version"].as_u64() {
+            Some(MANIFEST_VERSION) => (),
+            Some(version) => return Err(ManifestError::UnsupportedVersion(version)),
+            None => return Err(corrupt("it has no version")),
+        }
+
+        let items = json["items"]
+            .as_array()
+            .ok_or_else(|| corrupt("it has no items"))?
+            .iter()
+            .map(|item| parse_item(item).map_err(corrupt))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            items,
+        })
+    }
+
+    /// Writes the manifest back to disk. The old one is only replaced once the
+    /// new one is fully written.
+    pub fn save(&self) -> Result<(), ManifestError> {
+        let unwritable = |source| ManifestError::Unwritable {
+            path: self.path.clone(),
+            source,
+        };
+
+        let json = json!({
+            "version": MANIFEST_VERSION,
+            "items": self.items.iter().map(item_json).collect::<Vec<_>>(),
+        });
+        let text = serde_json::to_string_pretty(&json).expect("json values always serialize");
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(unwritable)?;
+        }
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, text).map_err(unwritable)?;
+        fs::rename(&temporary, &self.path).map_err(unwritable)
+    }
+
+    /// Remembers an install, hashing every file it wrote. Installing over
+    /// items that are already known replaces them with a single one, which
+    /// keeps the id of the oldest.
+    pub fn record(
+        &mut self,
+        link: &ParsedOcsUrl,
+        report: &InstallReport,
+    ) -> Result<&InstalledItem, ManifestError> {
+        let files = report
+            .files
+            .iter()
+            .map(|path| {
+                let (sha256, size) =
+                    hash_file(path).map_err(|source| ManifestError::Unhashable {
+                        path: path.clone(),
+                        source,
+                    })?;
+                Ok(InstalledFile {
+                    path: path.clone(),
+                    sha256,
+                    size,
+                })
+            })
+            .collect::<Result<Vec<_>, ManifestError>>()?;
+
+        let now = SystemTime::now();
+        let (replaced, kept) = self.items.drain(..).partition::<Vec<_>, _>(|item| {
+            report.items.iter().any(|path| item.items.contains(path))
+        });
+        self.items = kept;
+        let (id, installed_at) = match replaced.first() {
+            Some(old) => (old.id, old.installed_at),
+            None => (self.next_id(), now),
+        };
+
+        self.items.push(InstalledItem {
+            id,
+            link: link.clone(),
+            install_type: report.install_type,
+            destination: report.destination.clone(),
+            items: report.items.clone(),
+            files,
+            installed_at,
+            updated_at: now,
+            source_url: link.download_url.clone(),
+        });
+
+        Ok(self.items.last().expect("we just added it"))
+    }
+
+    /// Forgets about an item, returning it.
+    pub fn remove(&mut self, id: u64) -> Option<InstalledItem> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Every installed item, oldest first.
+    pub fn items(&self) -> &[InstalledItem] {
+        &self.items
+    }
+
+    /// Finds an item by its id.
+    pub fn get(&self, id: u64) -> Option<&InstalledItem> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    /// Finds the item that a file or directory on disk came from.
+    pub fn owner_of(&self, path: &Path) -> Option<&InstalledItem> {
+        self.items.iter().find(|item| item.owns(path))
+    }
+
+    /// Finds the items that were downloaded from `source_url`.
+    pub fn from_source<'a>(
+        &'a self,
+        source_url: &'a Url,
+    ) -> impl Iterator<Item = &'a InstalledItem> + 'a {
+        self.items
+            .iter()
+            .filter(move |item| &item.source_url == source_url)
+    }
+
+    /// Lists the items of an install type.
+    pub fn of_type(&self, install_type: InstallType) -> impl Iterator<Item = &InstalledItem> {
+        self.items
+            .iter()
+            .filter(move |item| item.install_type == install_type)
+    }
+
+    /// Lists the items in a category.
+    pub fn in_category(&self, category: InstallCategory) -> impl Iterator<Item = &InstalledItem> {
+        self.items
+            .iter()
+            .filter(move |item| item.install_type.category() == category)
+    }
+
+    fn next_id(&self) -> u64 {
+        self.items.iter().map(|item| item.id).max().unwrap_or(0) + 1
+    }
+}
+
+/// Hashes a file the way the manifest does, returning the hash and size.
+/// Symlinks aren't followed: their target is hashed instead.
+pub fn hash_file(path: &Path) -> io::Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+
+    let size = if path.symlink_metadata()?.is_symlink() {
+        let target = fs::read_link(path)?;
+        let target = target.as_os_str().as_bytes();
+        hasher.update(target);
+        target.len() as u64
+    } else {
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0; 64 * 1024];
+        let mut size = 0;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            size += read as u64;
+        }
+        size
+    };
+
+    let hash = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    Ok((hash, size))
+}
+
+fn item_json(item: &InstalledItem) -> Value {
+    json!({
+        "id": item.id,
+        "link": item.link.ocs_url.as_str(),
+        "install_type": item.install_type.aliases()[0],
+        "destination": item.destination.to_string_lossy(),
+        "items": item.items.iter().map(|item| item.to_string_lossy()).collect::<Vec<_>>(),
+        "files": item.files.iter().map(|file| json!({
+            "path": file.path.to_string_lossy(),
+            "sha256": file.sha256,
+            "size": file.size,
+        })).collect::<Vec<_>>(),
+        "installed_at": seconds(item.installed_at),
+        "updated_at": seconds(item.updated_at),
+        "source_url": item.source_url.as_str(),
+    })
+}
+
+fn parse_item(json: &Value) -> Result<InstalledItem, &'static str> {
+    let text = |key: &str| json[key].as_str().ok_or("an item is missing a field");
+    let time = |key: &str| {
+        json[key]
+            .as_u64()
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+            .ok_or("an item is missing a timestamp")
+    };
+    let paths = |value: &Value| -> Result<Vec<PathBuf>, &'static str> {
+        value
+            .as_array()
+            .ok_or("an item is missing its paths")?
+            .iter()
+            .map(|path| path.as_str().map(PathBuf::from).ok_or("a path isn't text"))
+            .collect()
+    };
+
+    let files = json["files"]
+        .as_array()
+        .ok_or("an item is missing its files")?
+        .iter()
+        .map(|file| {
+            Ok(InstalledFile {
+                path: file["path"].as_str().ok_or("a file has no path")?.into(),
+                sha256: file["sha256"].as_str().ok_or("a file has no hash")?.into(),
+                size: file["size"].as_u64().ok_or("a file has no size")?,
+            })
+        })
+        .collect::<Result<_, &'static str>>()?;
+
+    Ok(InstalledItem {
+        id: json["id"].as_u64().ok_or("an item has no id")?,
+        link: check_url(text("link")?.to_owned()).map_err(|_| "an item has a bad link")?,
+        install_type: InstallType::from_alias(text("install_type")?)
+            .map_err(|_| "an item has an unknown install type")?,
+        destination: text("destination")?.into(),
+        items: paths(&json["items"])?,
+        files,
+        installed_at: time("installed_at")?,
+        updated_at: time("updated_at")?,
+        source_url: Url::parse(text("source_url")?).map_err(|_| "an item has a bad url")?,
+    })
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,254 @@
+//! A client for the [Open Collaboration Services](https://www.freedesktop.org/wiki/Specifications/open-collaboration-services/)
+//! API, which Pling and friends use to describe the things they host.
+//!
+//! We only need the `content/data` endpoints, which tell us an item's name,
+//! author, screenshots and so on.
+//!
+//! `ocs://` links don't say which item they're for: Pling's download URLs look
+//! like `https://files.pling.com/api/files/download/j/{token}/Nordic.tar.xz`,
+//! and the token is only meant for the download server. So
+//! [`OcsClient::content_for_link`] searches the store for the file's name, and
+//! takes the item that offers a file with exactly that name. A store page,
+//! like `https://www.pling.com/p/1267246/`, does carry the id, so use
+//! [`OcsClient::content_for_page`] when there is one.
+use crate::handler::DownloadOptions;
+use crate::types::ParsedOcsUrl;
+
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+
+/// Pling's OCS API. It's used by the Pling store, the KDE Store, and others.
+pub const PLING_API: &str = "https://api.pling.com/ocs/v1/";
+
+/// Represents a failure when talking to an OCS API.
+#[derive(Error, Debug)]
+pub enum OcsApiError {
+    #[error("The page doesn't say which store item it's for.")]
+    NoContentId,
+    #[error("`{0}` isn't a store item id.")]
+    BadContentId(String),
+    #[error("The store doesn't have an item with a file called `{0}`.")]
+    NoMatchingContent(String),
+    #[error("The OCS API responded with HTTP {status} for `{url}`.")]
+    HttpStatus { url: Url, status: u16 },
+    #[error("Couldn't reach the OCS API at `{url}`: {message}")]
+    Transport { url: Url, message: String },
+    #[error("The OCS API said no ({code}): {message}")]
+    Api { code: u64, message: String },
+    #[error("The OCS API gave back something we don't understand: {0}")]
+    BadResponse(String),
+    #[error(transparent)]
+    UrlParsingError(#[from] url::ParseError),
+}
+
+/// What the store knows about an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentItem {
+    pub id: String,
+    pub name: String,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub description: Option<String>,
+    /// Full-size screenshots, in the order the author gave them.
+    pub previews: Vec<Url>,
+    /// Thumbnails of the screenshots.
+    pub small_previews: Vec<Url>,
+    /// The names of the files it offers for download.
+    pub files: Vec<String>,
+}
+
+/// Talks to an OCS v1 API.
+///
+/// The base URL is configurable, so it works with any store (or a mock one).
+#[derive(Debug, Clone)]
+pub struct OcsClient {
+    base_url: Url,
+    agent: ureq::Agent,
+}
+
+impl Default for OcsClient {
+    fn default() -> Self {
+        Self::new(Url::parse(PLING_API).expect("the Pling API URL is valid"))
+    }
+}
+
+impl OcsClient {
+    /// Makes a client for the OCS API at `base_url`, like
+    /// `https://api.pling.com/ocs/v1/`.
+    pub fn new(base_url: Url) -> Self {
+        Self::with_options(base_url, &DownloadOptions::default())
+    }
+
+    /// Makes a client with custom timeouts.
+    pub fn with_options(mut base_url: Url, options: &DownloadOptions) -> Self {
+        // without the slash, `join` would replace the last part of the path
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(options.connect_timeout)
+            .timeout_read(options.read_timeout)
+            .build();
+
+        Self { base_url, agent }
+    }
+
+    /// Gets everything the store knows about the item with the given id.
+    pub fn content(&self, id: &str) -> Result<ContentItem, OcsApiError> {
+        // ids go into the path, so they can't be allowed to change it
+        let is_id = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric());
+        if !is_id {
+            return Err(OcsApiError::BadContentId(id.to_owned()));
+        }
+
+        let url = self.base_url.join("content/data/")?.join(id)?;
+        let json = self.get(url, &[])?;
+
+        parse_content(&json)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| OcsApiError::BadResponse("`data` is empty".into()))
+    }
+
+    /// Gets everything the store knows about the item on a store page.
+    pub fn content_for_page(&self, page: &Url) -> Result<ContentItem, OcsApiError> {
+        let id = content_id_from_page(page).ok_or(OcsApiError::NoContentId)?;
+        self.content(&id)
+    }
+
+    /// Finds the store item that a link downloads, by searching for its file.
+    ///
+    /// The store is asked about the file's name, so only do this for links
+    /// that come from that store (see [`is_store_download`]).
+    pub fn content_for_link(&self, link: &ParsedOcsUrl) -> Result<ContentItem, OcsApiError> {
+        let filename = link
+            .download_filename()
+            .map_err(|_| OcsApiError::NoMatchingContent(link.download_url.to_string()))?;
+        // `Nordic-darker.tar.xz` is searched for as `Nordic-darker`
+        let term = filename.split('.').next().unwrap_or(&filename);
+
+        let url = self.base_url.join("content/data")?;
+        let json = self.get(url, &[("search", term)])?;
+
+        parse_content(&json)?
+            .into_iter()
+            .find(|item| item.files.contains(&filename))
+            .ok_or(OcsApiError::NoMatchingContent(filename))
+    }
+
+    /// Sends a GET request to the API, asking for JSON.
+    fn get(&self, mut url: Url, query: &[(&str, &str)]) -> Result<Value, OcsApiError> {
+        url.query_pairs_mut()
+            .extend_pairs(query)
+            .append_pair("format", "json");
+
+        let response = match self.agent.get(url.as_str()).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(status, _)) => {
+                return Err(OcsApiError::HttpStatus { url, status })
+            }
+            Err(ureq::Error::Transport(transport)) => {
+                return Err(OcsApiError::Transport {
+                    url,
+                    message: transport.to_string(),
+                })
+            }
+        };
+
+        serde_json::from_reader(response.into_reader())
+            .map_err(|e| OcsApiError::BadResponse(e.to_string()))
+    }
+}
+
+/// Checks if a download comes from a store that [`PLING_API`] knows about,
+/// like `files.pling.com` or `dl.opendesktop.org`.
+pub fn is_store_download(url: &Url) -> bool {
+    url.host_str().is_some_and(|host| {
+        ["pling.com", "opendesktop.org"].iter().any(|store| {
+            host == *store
+                || host
+                    .strip_suffix(store)
+                    .is_some_and(|sub| sub.ends_with('.'))
+        })
+    })
+}
+
+/// Finds the id of the item on a store page, like `https://www.pling.com/p/1267246/`.
+pub fn content_id_from_page(page: &Url) -> Option<String> {
+    let is_id = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    let segments: Vec<&str> = page.path_segments()?.collect();
+    segments
+        .windows(2)
+        .find(|pair| pair[0] == "p" && is_id(pair[1]))
+        .map(|pair| pair[1].to_owned())
+}
+
+/// Pulls the [`ContentItem`]s out of an OCS response.
+///
+/// The spec nests everything under `ocs.meta` and `ocs.data.content`, while
+/// Pling flattens it into `status` and a `data` list. We take both.
+fn parse_content(json: &Value) -> Result<Vec<ContentItem>, OcsApiError> {
+    let json = json.get("ocs").unwrap_or(json);
+    let meta = json.get("meta").unwrap_or(json);
+
+    if text(meta, "status").as_deref() != Some("ok") {
+        return Err(OcsApiError::Api {
+            code: meta
+                .get("statuscode")
+                .and_then(|code| code.as_u64().or_else(|| code.as_str()?.parse().ok()))
+                .unwrap_or_default(),
+            message: text(meta, "message").unwrap_or_default(),
+        });
+    }
+
+    let mut data = json
+        .get("data")
+        .ok_or_else(|| OcsApiError::BadResponse("there's no `data`".into()))?;
+    if let Some(content) = data.get("content") {
+        data = content;
+    }
+
+    match data.as_array() {
+        Some(list) => list.iter().map(parse_item).collect(),
+        None => Ok(vec![parse_item(data)?]),
+    }
+}
+
+/// Pulls one [`ContentItem`] out of an OCS response.
+fn parse_item(data: &Value) -> Result<ContentItem, OcsApiError> {
+    Ok(ContentItem {
+        id: text(data, "id").ok_or_else(|| OcsApiError::BadResponse("there's no `id`".into()))?,
+        name: text(data, "name").unwrap_or_default(),
+        author: text(data, "personid"),
+        version: text(data, "version"),
+        license: text(data, "license"),
+        description: text(data, "description"),
+        previews: numbered_urls(data, "previewpic"),
+        small_previews: numbered_urls(data, "smallpreviewpic"),
+        files: (1..)
+            .map_while(|i| text(data, &format!("downloadname{i}")))
+            .collect(),
+    })
+}
+
+/// Gets a field as text, whether it was sent as a string or a number.
+/// Empty strings don't count.
+fn text(json: &Value, key: &str) -> Option<String> {
+    match json.get(key)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Gets `{prefix}1`, `{prefix}2`, ... until one is missing.
+fn numbered_urls(json: &Value, prefix: &str) -> Vec<Url> {
+    (1..)
+        .map_while(|i| text(json, &format!("{prefix}{i}")))
+        .filter_map(|url| Url::parse(&url).ok())
+        .collect()
+}
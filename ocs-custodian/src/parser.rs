@@ -1,6 +1,6 @@
 //! A way to parse `ocs://` URLS.
 #[allow(unused_imports)]
-use crate::types::{Command, OcsParsingError, ParsedOcsUrl, Scheme};
+use crate::types::{Command, InstallType, OcsParsingError, ParsedOcsUrl, Scheme};
 
 use std::collections::HashMap;
 use std::ops::Deref;
@@ -9,6 +9,7 @@ use url::Url;
 use urlencoding::decode;
 
 /// Takes in an `ocs://` URL and parses it. Provides a ParsedOcsUrl.
+/// `xdg://` links are accepted too, since they share the same grammar.
 ///
 /// As-is, OCS links should be lowercase in their implementation.
 /// ```
@@ -23,6 +24,15 @@ pub fn check_url(url: String) -> Result<ParsedOcsUrl, OcsParsingError> {
     // make a hashmap out of it
     let parameters: HashMap<String, String> = ocs_url.query_pairs().into_owned().collect();
 
+    // the install type has to be one we actually know how to install
+    let install_type_alias = match parameters.get("type") {
+        None => return Err(OcsParsingError::NoInstallType),
+        Some(alias) if alias.is_empty() => return Err(OcsParsingError::NoInstallType),
+        Some(alias) => alias.to_owned(),
+    };
+    let install_type = InstallType::try_from(install_type_alias.as_str())
+        .map_err(|_| OcsParsingError::UnknownInstallType(install_type_alias.clone()))?;
+
     // create a new instance of ParsedOcsUrl to fill out
     let parsed_ocs_url = ParsedOcsUrl {
         ocs_url: ocs_url.clone(),
@@ -35,10 +45,8 @@ pub fn check_url(url: String) -> Result<ParsedOcsUrl, OcsParsingError> {
             None => return Err(OcsParsingError::NoDownloadUrl),
             Some(given) => url::Url::from_str(given)?,
         },
-        install_type: match parameters.get("type") {
-            None => return Err(OcsParsingError::NoInstallType),
-            Some(install_type) => install_type.to_owned(), // TODO: do a prelim check if install type is known for installation
-        },
+        install_type,
+        install_type_alias,
         filename: parameters
             .get("filename")
             .map(|filename| filename.to_owned()),
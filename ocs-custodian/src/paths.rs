@@ -0,0 +1,217 @@
+//! Turns the placeholder-filled install paths into real ones.
+//!
+//! Install types describe their destinations with templates like
+//! `$XDG_DATA_HOME/icons`. A [`PathResolver`] expands these using the
+//! environment it was given, falling back to the defaults from the
+//! [XDG Base Directory spec](https://specifications.freedesktop.org/basedir-spec/latest/).
+//!
+//! Personal folders like `$XDG_MUSIC_DIR` come from `xdg-user-dirs`, so that
+//! music ends up in `~/Música` on a Portuguese system, for example.
+use crate::types::install_type::InstallStrategy;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The name of our directory inside of `$XDG_DATA_HOME`.
+pub const APP_DIR_NAME: &str = "amizade";
+
+/// Represents a failure to turn an install path template into a real path.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PathError {
+    #[error("The `HOME` environment variable isn't set to an absolute path, so we can't find your home directory.")]
+    NoHome,
+    #[error("An unknown placeholder was found in the install path: `{0}`")]
+    UnknownPlaceholder(String),
+}
+
+/// Swaps the sandbox's XDG variables for the host's when running in a
+/// Flatpak, since that's where things get installed for real. Flatpak passes
+/// the host's along as `HOST_XDG_DATA_HOME` and friends, but only when they're
+/// set; otherwise the host uses the defaults in `$HOME`.
+pub(crate) fn host_vars(mut vars: HashMap<String, String>) -> HashMap<String, String> {
+    if !vars.contains_key("FLATPAK_ID") {
+        return vars;
+    }
+
+    for name in ["XDG_DATA_HOME", "XDG_CONFIG_HOME"] {
+        match vars.remove(&format!("HOST_{name}")) {
+            Some(host) => vars.insert(name.to_owned(), host),
+            None => vars.remove(name),
+        };
+    }
+
+    vars
+}
+
+/// Expands the `$HOME`, `$XDG_DATA_HOME`, `$XDG_CONFIG_HOME`, `$APP_DATA`,
+/// `$KDEHOME` and `xdg-user-dirs` (like `$XDG_MUSIC_DIR`) placeholders in
+/// install paths.
+///
+/// The environment is injectable, so you can point it somewhere harmless:
+/// ```
+/// use ocs_custodian::paths::PathResolver;
+/// use std::collections::HashMap;
+/// use std::path::PathBuf;
+///
+/// let resolver = PathResolver::with_vars(HashMap::from([("HOME".into(), "/home/me".into())]));
+/// assert_eq!(
+///     resolver.resolve("$XDG_DATA_HOME/icons"),
+///     Ok(PathBuf::from("/home/me/.local/share/icons"))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathResolver {
+    vars: HashMap<String, String>,
+}
+
+impl PathResolver {
+    /// Creates a resolver that uses the current process' environment.
+    ///
+    /// Inside of a Flatpak, the XDG variables point into the sandbox, so the
+    /// host's are used instead. The Flatpak needs access to the home folder
+    /// for that.
+    pub fn from_env() -> Self {
+        Self::with_vars(host_vars(std::env::vars().collect()))
+    }
+
+    /// Creates a resolver that only knows about the given variables.
+    pub fn with_vars(vars: HashMap<String, String>) -> Self {
+        Self { vars }
+    }
+
+    /// The user's home directory.
+    pub fn home(&self) -> Result<PathBuf, PathError> {
+        self.absolute_var("HOME").ok_or(PathError::NoHome)
+    }
+
+    /// `$XDG_DATA_HOME`, defaulting to `$HOME/.local/share`.
+    pub fn xdg_data_home(&self) -> Result<PathBuf, PathError> {
+        self.xdg_var("XDG_DATA_HOME", ".local/share")
+    }
+
+    /// `$XDG_CONFIG_HOME`, defaulting to `$HOME/.config`.
+    pub fn xdg_config_home(&self) -> Result<PathBuf, PathError> {
+        self.xdg_var("XDG_CONFIG_HOME", ".config")
+    }
+
+    /// `$APP_DATA` is where Amizade keeps its own stuff: `$XDG_DATA_HOME/amizade`.
+    pub fn app_data(&self) -> Result<PathBuf, PathError> {
+        Ok(self.xdg_data_home()?.join(APP_DIR_NAME))
+    }
+
+    /// `$KDEHOME`, defaulting to `$HOME/.kde` like KDE 4 did.
+    pub fn kde_home(&self) -> Result<PathBuf, PathError> {
+        self.xdg_var("KDEHOME", ".kde")
+    }
+
+    /// One of the user's personal folders, like `XDG_MUSIC_DIR`.
+    ///
+    /// These are read from `$XDG_CONFIG_HOME/user-dirs.dirs`. If it's not
+    /// listed there, we'll take the environment variable of the same name,
+    /// then fall back to the usual English name in `$HOME`.
+    pub fn user_dir(&self, name: &str) -> Result<PathBuf, PathError> {
+        let fallback = match name {
+            "XDG_DESKTOP_DIR" => "Desktop",
+            "XDG_DOCUMENTS_DIR" => "Documents",
+            "XDG_DOWNLOAD_DIR" => "Downloads",
+            "XDG_MUSIC_DIR" => "Music",
+            "XDG_PICTURES_DIR" => "Pictures",
+            "XDG_PUBLICSHARE_DIR" => "Public",
+            "XDG_TEMPLATES_DIR" => "Templates",
+            "XDG_VIDEOS_DIR" => "Videos",
+            other => return Err(PathError::UnknownPlaceholder(other.into())),
+        };
+
+        let home = self.home()?;
+        let user_dirs = self.xdg_config_home()?.join("user-dirs.dirs");
+
+        if let Some(dir) = std::fs::read_to_string(user_dirs)
+            .ok()
+            .and_then(|contents| parse_user_dir(&contents, name, &home))
+        {
+            return Ok(dir);
+        }
+
+        match self.absolute_var(name) {
+            Some(path) => Ok(path),
+            None => Ok(home.join(fallback)),
+        }
+    }
+
+    /// Expands the placeholder at the start of `template`, if there is one.
+    pub fn resolve(&self, template: &str) -> Result<PathBuf, PathError> {
+        let Some(template) = template.strip_prefix('$') else {
+            return Ok(PathBuf::from(template));
+        };
+
+        let (placeholder, rest) = template.split_once('/').unwrap_or((template, ""));
+        let base = match placeholder {
+            "HOME" => self.home()?,
+            "XDG_DATA_HOME" => self.xdg_data_home()?,
+            "XDG_CONFIG_HOME" => self.xdg_config_home()?,
+            "APP_DATA" => self.app_data()?,
+            "KDEHOME" => self.kde_home()?,
+            user_dir if user_dir.starts_with("XDG_") && user_dir.ends_with("_DIR") => {
+                self.user_dir(user_dir)?
+            }
+            other => return Err(PathError::UnknownPlaceholder(other.into())),
+        };
+
+        if rest.is_empty() {
+            Ok(base)
+        } else {
+            Ok(base.join(rest))
+        }
+    }
+
+    /// Gets the real destination for an install type.
+    pub fn install_path(&self, install_type: &impl InstallStrategy) -> Result<PathBuf, PathError> {
+        self.resolve(&install_type.get_install_path())
+    }
+
+    /// Gets an environment variable, but only if it's an absolute path.
+    /// The XDG spec says to ignore relative ones.
+    fn absolute_var(&self, name: &str) -> Option<PathBuf> {
+        self.vars
+            .get(name)
+            .map(Path::new)
+            .filter(|path| path.is_absolute())
+            .map(Path::to_path_buf)
+    }
+
+    /// Gets an environment variable, or `$HOME/{fallback}` if it's not usable.
+    fn xdg_var(&self, name: &str, fallback: &str) -> Result<PathBuf, PathError> {
+        match self.absolute_var(name) {
+            Some(path) => Ok(path),
+            None => Ok(self.home()?.join(fallback)),
+        }
+    }
+}
+
+/// Finds `name` in the contents of a `user-dirs.dirs` file.
+///
+/// Lines look like `XDG_MUSIC_DIR="$HOME/Music"`. Values are either relative
+/// to `$HOME` or absolute, and nothing else is allowed.
+fn parse_user_dir(contents: &str, name: &str, home: &Path) -> Option<PathBuf> {
+    // later lines win, like when the shell sources the file
+    contents
+        .lines()
+        .rev()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| key.trim() == name)
+        .filter_map(|(_, value)| {
+            let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+            if let Some(relative) = value.strip_prefix("$HOME") {
+                Some(home.join(relative.trim_start_matches('/')))
+            } else if value.starts_with('/') {
+                Some(PathBuf::from(value))
+            } else {
+                None
+            }
+        })
+        .next()
+}
@@ -0,0 +1,319 @@
+//! Works out what an install would do, without doing it.
+//!
+//! [`Installer::plan`] downloads a link's file and looks inside of it, so that
+//! the [`InstallPlan`] can list every file that would be written. The download
+//! is kept, so that [`Installer::install_plan`] installs exactly what was
+//! shown.
+use crate::archive::{list, ArchiveEntry, ArchiveFormat};
+use crate::fs_util::unique_dir;
+use crate::handler::DownloadProgress;
+use crate::installer::{Conflict, ConflictPolicy, InstallError, Installer};
+use crate::legacy::find_legacy_item;
+use crate::manifest::hash_file;
+use crate::paths::PathResolver;
+use crate::progress::Cancellation;
+use crate::types::install_type::{InstallType, Styling};
+use crate::types::{Command, ParsedOcsUrl};
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process;
+
+/// Something that happens after the files are in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum PostInstallAction {
+    /// Runs `fc-cache` on the destination, so that new fonts show up.
+    UpdateFontCache,
+}
+
+impl PostInstallAction {
+    /// What should happen after installing something of `install_type`.
+    pub fn for_install_type(install_type: InstallType) -> Vec<Self> {
+        match install_type {
+            InstallType::Styling(Styling::Fonts) => vec![Self::UpdateFontCache],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Does the action for things installed into `destination`.
+    pub fn run(&self, destination: &Path) -> io::Result<()> {
+        match self {
+            Self::UpdateFontCache => {
+                let status = process::Command::new("fc-cache")
+                    .arg(destination)
+                    .status()?;
+                match status.success() {
+                    true => Ok(()),
+                    false => Err(io::Error::other(format!("fc-cache failed: {status}"))),
+                }
+            }
+        }
+    }
+}
+
+/// Whether a planned file is new, or replaces one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum FileAction {
+    Create,
+    Overwrite,
+}
+
+/// A file that an install would write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub action: FileAction,
+}
+
+/// Everything an install would do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstallPlan {
+    pub link: ParsedOcsUrl,
+    pub install_type: InstallType,
+    /// The directory that everything would go into.
+    pub destination: PathBuf,
+    pub conflict_policy: ConflictPolicy,
+    /// The things that would be placed right inside of `destination`.
+    pub items: Vec<PathBuf>,
+    /// Every file that would be written, including those inside of `items`.
+    pub files: Vec<PlannedFile>,
+    /// Anything already in the way, and what the policy would do with it.
+    pub conflicts: Vec<Conflict>,
+    /// How big the download is.
+    pub download_size: u64,
+    /// How much space the written files would take up.
+    pub install_size: u64,
+    pub post_install: Vec<PostInstallAction>,
+    /// Everything in the download, relative to `destination`. That's just the
+    /// file itself if it isn't unpacked.
+    pub contents: Vec<ArchiveEntry>,
+    /// Where the download is kept until the plan is installed or discarded.
+    pub staged: PathBuf,
+    /// The SHA-256 of the download, so it can be checked before installing.
+    pub sha256: String,
+    /// Old copies of the items in a legacy location like `~/.themes`, which
+    /// are in the way too.
+    pub legacy: Vec<PathBuf>,
+}
+
+impl InstallPlan {
+    /// Switches the plan to another conflict policy, working out again what
+    /// it would do. Nothing is downloaded again.
+    pub fn set_conflict_policy(&mut self, policy: ConflictPolicy) {
+        self.conflict_policy = policy;
+        self.resolve_conflicts();
+    }
+
+    /// Works out where each of the `contents` would go with the plan's
+    /// conflict policy, and what's in the way.
+    fn resolve_conflicts(&mut self) {
+        self.items.clear();
+        self.files.clear();
+        self.conflicts.clear();
+        self.install_size = 0;
+
+        for name in top_level(&self.contents) {
+            let inside: Vec<_> = self
+                .contents
+                .iter()
+                .filter(|entry| entry.path.starts_with(&name))
+                .collect();
+            let is_dir = inside
+                .iter()
+                .any(|entry| entry.is_dir || entry.path != Path::new(&name));
+
+            let target = self.destination.join(&name);
+            let placed_at = match self.conflict_policy.resolve(&target, is_dir) {
+                Some(conflict) => {
+                    let placed_at = conflict.new_location();
+                    self.conflicts.push(conflict);
+                    placed_at
+                }
+                None => Some(target),
+            };
+            let Some(placed_at) = placed_at else {
+                continue;
+            };
+            if let Some(legacy) = self.legacy.iter().find(|item| item.ends_with(&name)) {
+                let conflict = self.conflict_policy.resolve_legacy(legacy, &placed_at);
+                let skipped = conflict.policy == ConflictPolicy::Skip;
+                self.conflicts.push(conflict);
+                if skipped {
+                    continue;
+                }
+            }
+
+            for entry in inside.into_iter().filter(|entry| !entry.is_dir) {
+                let relative = entry.path.strip_prefix(&name).expect("filtered above");
+                let path = match relative.as_os_str().is_empty() {
+                    true => placed_at.clone(),
+                    false => placed_at.join(relative),
+                };
+
+                // with backups, the old file is moved away first
+                let replaces = self.conflict_policy == ConflictPolicy::Overwrite
+                    && path.symlink_metadata().is_ok();
+
+                self.install_size += entry.size;
+                self.files.push(PlannedFile {
+                    path,
+                    size: entry.size,
+                    action: match replaces {
+                        true => FileAction::Overwrite,
+                        false => FileAction::Create,
+                    },
+                });
+            }
+            self.items.push(placed_at);
+        }
+
+        self.post_install = match self.items.is_empty() {
+            true => Vec::new(),
+            false => PostInstallAction::for_install_type(self.install_type),
+        };
+    }
+
+    /// Removes the plan's download, for when it won't be installed after
+    /// all. [`Installer::install_plan`] does this by itself.
+    ///
+    /// Plans can be read back from JSON, so only a directory that's really in
+    /// our staging area is ever removed.
+    pub fn discard(&self, resolver: &PathResolver) {
+        if let Ok(dir) = self.staging_dir(resolver) {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    /// The directory the plan's download is in, which has to be one of its
+    /// own right inside of the staging area.
+    pub(crate) fn staging_dir(&self, resolver: &PathResolver) -> Result<PathBuf, InstallError> {
+        let root = resolver.app_data()?.join("staging");
+        let not_staged = || InstallError::NotStaged(self.staged.clone());
+
+        self.staged.file_name().ok_or_else(not_staged)?;
+        let dir = self.staged.parent().ok_or_else(not_staged)?;
+        match (dir.parent(), dir.components().next_back()) {
+            (Some(parent), Some(Component::Normal(_))) if parent == root => Ok(dir.to_path_buf()),
+            _ => Err(not_staged()),
+        }
+    }
+}
+
+impl Installer {
+    /// Works out what [`Installer::install`] would do with the link, using the
+    /// same settings.
+    ///
+    /// This isn't free of side effects: the file is downloaded into a
+    /// directory of its own in `$APP_DATA/staging`, and kept there for
+    /// [`Installer::install_plan`]. That way the bytes that get installed are
+    /// the ones that were looked at, and big downloads don't have to fit in
+    /// memory. Call [`InstallPlan::discard`] if the plan won't be installed.
+    /// Nothing outside of staging is touched.
+    ///
+    /// `progress` hears about the download as it happens.
+    pub fn plan(
+        &self,
+        link: &ParsedOcsUrl,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<InstallPlan, InstallError> {
+        let destination = self.checked_destination(link)?;
+
+        let staging_root = self.resolver.app_data()?.join("staging");
+        let staging = unique_dir(&staging_root, "plan").map_err(|source| InstallError::Io {
+            path: staging_root,
+            source,
+        })?;
+
+        let result = link
+            .download_cancellable(
+                &staging,
+                &self.capped_download_options(),
+                &Cancellation::new(),
+                progress,
+            )
+            .map_err(InstallError::from)
+            .and_then(|downloaded| self.plan_from(link, downloaded, destination));
+
+        // the download is only kept for a plan that can be installed
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&staging);
+        }
+
+        result
+    }
+
+    /// Does the actual work of [`Installer::plan`], once the file is
+    /// downloaded.
+    fn plan_from(
+        &self,
+        link: &ParsedOcsUrl,
+        downloaded: PathBuf,
+        destination: PathBuf,
+    ) -> Result<InstallPlan, InstallError> {
+        let io_error = |source| InstallError::Io {
+            path: downloaded.clone(),
+            source,
+        };
+        let (sha256, download_size) = hash_file(&downloaded).map_err(io_error)?;
+
+        let format = ArchiveFormat::detect(&downloaded).map_err(io_error)?;
+        let entries = match format {
+            Some(_) if link.command == Command::Install => list(&downloaded, &self.extract_limits)?,
+            _ => vec![ArchiveEntry {
+                path: PathBuf::from(link.download_filename()?),
+                size: download_size,
+                is_dir: false,
+            }],
+        };
+
+        let mut legacy = Vec::new();
+        for name in top_level(&entries) {
+            let found = find_legacy_item(&link.install_type, &self.resolver, &destination, &name)?;
+            legacy.extend(found);
+        }
+
+        let mut plan = InstallPlan {
+            link: link.clone(),
+            install_type: link.install_type,
+            destination,
+            conflict_policy: self.conflict_policy,
+            items: Vec::new(),
+            files: Vec::new(),
+            conflicts: Vec::new(),
+            download_size,
+            install_size: 0,
+            post_install: Vec::new(),
+            contents: entries,
+            staged: downloaded,
+            sha256,
+            legacy,
+        };
+        plan.resolve_conflicts();
+
+        Ok(plan)
+    }
+}
+
+/// The names of the things at the top of the archive, in order.
+fn top_level(entries: &[ArchiveEntry]) -> Vec<String> {
+    let mut names = Vec::new();
+
+    for entry in entries {
+        if let Some(first) = entry.path.components().next() {
+            let first = first.as_os_str().to_string_lossy().into_owned();
+            if !names.contains(&first) {
+                names.push(first);
+            }
+        }
+    }
+
+    names
+}
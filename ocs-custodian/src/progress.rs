@@ -0,0 +1,58 @@
+//! Lets frontends follow an install as it happens, and stop it.
+//!
+//! [`Installer::install_with`] sends an [`InstallEvent`] whenever the install
+//! gets further, and checks a [`Cancellation`] along the way. Both can be sent
+//! across threads, so a GUI can run the install in the background and still
+//! show how it's going.
+//!
+//! [`Installer::install_with`]: crate::installer::Installer::install_with
+use crate::handler::DownloadProgress;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The steps of an install, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum InstallStage {
+    Downloading,
+    /// Only happens for archives.
+    Extracting,
+    /// Moving things into the destination. It's too late to cancel by now.
+    Placing,
+    /// Remembering the install, then running any [`PostInstallAction`]s.
+    ///
+    /// [`PostInstallAction`]: crate::plan::PostInstallAction
+    Finishing,
+}
+
+/// Something that happened during an install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallEvent {
+    /// The install moved on to another stage.
+    Stage(InstallStage),
+    /// Another chunk was downloaded.
+    Download(DownloadProgress),
+}
+
+/// Asks an install to stop from somewhere else, like another thread.
+///
+/// Clones share the same flag, so cancelling one cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks whatever's using this to stop as soon as it can.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,389 @@
+#![allow(unused)]
+use crate::archive::{
+    extract, extract_cancellable, list, ArchiveError, ArchiveFormat, ExtractLimits,
+};
+use crate::progress::Cancellation;
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// An entry to put in a test tarball.
+enum Entry<'a> {
+    File(&'a str, &'a [u8]),
+    Dir(&'a str),
+    Symlink(&'a str, &'a str),
+    HardLink(&'a str, &'a str),
+}
+
+/// Builds a tarball by hand, so that we can write names that `tar::Builder`
+/// would refuse.
+fn tarball(entries: &[Entry]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+
+    for entry in entries {
+        let mut header = tar::Header::new_gnu();
+        let (name, data): (&str, &[u8]) = match entry {
+            Entry::File(name, data) => {
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_mode(0o644);
+                (name, data)
+            }
+            Entry::Dir(name) => {
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_mode(0o755);
+                (name, b"")
+            }
+            Entry::Symlink(name, target) | Entry::HardLink(name, target) => {
+                header.set_entry_type(match entry {
+                    Entry::Symlink(..) => tar::EntryType::Symlink,
+                    _ => tar::EntryType::Link,
+                });
+                header.as_gnu_mut().unwrap().linkname[..target.len()]
+                    .copy_from_slice(target.as_bytes());
+                (name, b"")
+            }
+        };
+
+        header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    builder.into_inner().unwrap()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Writes `data` to a file with a misleading name, and unpacks it.
+#[cfg(test)]
+fn unpack(
+    data: &[u8],
+    limits: &ExtractLimits,
+) -> (tempfile::TempDir, Result<Vec<PathBuf>, ArchiveError>) {
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir.path().join("download.bin");
+    fs::write(&archive, data).unwrap();
+
+    let result = extract(&archive, &dir.path().join("out"), limits);
+    (dir, result)
+}
+
+#[test]
+fn detects_formats_by_content() {
+    let tar = tarball(&[Entry::File("a", b"a")]);
+    assert_eq!(ArchiveFormat::from_magic(&tar), Some(ArchiveFormat::Tar));
+    assert_eq!(
+        ArchiveFormat::from_magic(&gzip(&tar)),
+        Some(ArchiveFormat::TarGz)
+    );
+    assert_eq!(
+        ArchiveFormat::from_magic(&zstd::encode_all(&tar[..], 0).unwrap()),
+        Some(ArchiveFormat::TarZst)
+    );
+    assert_eq!(
+        ArchiveFormat::from_magic(b"\xfd7zXZ\x00rest"),
+        Some(ArchiveFormat::TarXz)
+    );
+    assert_eq!(
+        ArchiveFormat::from_magic(b"BZh91AY&SY"),
+        Some(ArchiveFormat::TarBz2)
+    );
+    assert_eq!(
+        ArchiveFormat::from_magic(b"PK\x03\x04"),
+        Some(ArchiveFormat::Zip)
+    );
+    assert_eq!(ArchiveFormat::from_magic(b"\x89PNG\r\n"), None);
+}
+
+#[test]
+fn extract_tarballs() {
+    let tar = tarball(&[
+        Entry::Dir("Nordic/"),
+        Entry::Dir("Nordic/gtk-3.0/"),
+        Entry::File("Nordic/gtk-3.0/gtk.css", b"* {}"),
+        Entry::Symlink("Nordic/gtk-4.0", "gtk-3.0"),
+        Entry::File("README", b"hi"),
+    ]);
+    let compressed = [
+        gzip(&tar),
+        zstd::encode_all(&tar[..], 0).unwrap(),
+        {
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+            encoder.write_all(&tar).unwrap();
+            encoder.finish().unwrap()
+        },
+        {
+            let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::best());
+            encoder.write_all(&tar).unwrap();
+            encoder.finish().unwrap()
+        },
+    ];
+
+    for data in compressed {
+        let (dir, result) = unpack(&data, &ExtractLimits::default());
+        let out = dir.path().join("out");
+
+        assert_eq!(
+            result.unwrap(),
+            vec![out.join("Nordic"), out.join("README")]
+        );
+        assert_eq!(
+            fs::read(out.join("Nordic/gtk-4.0/gtk.css")).unwrap(),
+            b"* {}"
+        );
+    }
+}
+
+#[test]
+fn extract_zips() {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    zip.add_directory("Papirus/", options).unwrap();
+    zip.start_file("Papirus/index.theme", options).unwrap();
+    zip.write_all(b"[Icon Theme]").unwrap();
+    zip.add_symlink("Papirus/link.theme", "index.theme", options)
+        .unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let (dir, result) = unpack(&data, &ExtractLimits::default());
+    let out = dir.path().join("out");
+
+    assert_eq!(result.unwrap(), vec![out.join("Papirus")]);
+    assert_eq!(
+        fs::read(out.join("Papirus/link.theme")).unwrap(),
+        b"[Icon Theme]"
+    );
+}
+
+#[test]
+fn refuse_paths_that_escape() {
+    for name in ["../evil", "a/../../evil", "/tmp/evil"] {
+        let (dir, result) = unpack(
+            &tarball(&[Entry::File(name, b"x")]),
+            &ExtractLimits::default(),
+        );
+
+        assert!(
+            matches!(result, Err(ArchiveError::UnsafePath(_))),
+            "{name}: {result:?}"
+        );
+        assert!(!dir.path().join("evil").exists());
+    }
+}
+
+#[test]
+fn refuse_zip_paths_that_escape() {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("../evil", zip::write::FileOptions::default())
+        .unwrap();
+    zip.write_all(b"x").unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let (dir, result) = unpack(&data, &ExtractLimits::default());
+    assert!(matches!(result, Err(ArchiveError::UnsafePath(_))));
+    assert!(!dir.path().join("evil").exists());
+}
+
+#[test]
+fn refuse_links_that_escape() {
+    for target in ["/etc/passwd", "../../evil", "a/../../../evil"] {
+        let (_dir, result) = unpack(
+            &tarball(&[Entry::Symlink("theme/link", target)]),
+            &ExtractLimits::default(),
+        );
+
+        assert!(
+            matches!(result, Err(ArchiveError::UnsafeLink { .. })),
+            "{target}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn refuse_chains_of_links_that_escape() {
+    // each of these stays inside on its own, but together they don't
+    let chains: [&[Entry]; 3] = [
+        &[Entry::Symlink("a/b", ".."), Entry::Symlink("a/c", "b/..")],
+        // the order doesn't matter
+        &[Entry::Symlink("a/c", "b/.."), Entry::Symlink("a/b", "..")],
+        &[
+            Entry::Symlink("a/b", "."),
+            Entry::Symlink("a/c", "b/b/b/.."),
+            Entry::Symlink("a/d", "c/.."),
+        ],
+    ];
+    for entries in chains {
+        let (_dir, result) = unpack(&tarball(entries), &ExtractLimits::default());
+        assert!(
+            matches!(result, Err(ArchiveError::UnsafeLink { .. })),
+            "{result:?}"
+        );
+    }
+
+    // so do links that go around in circles
+    let (_dir, result) = unpack(
+        &tarball(&[Entry::Symlink("a", "b"), Entry::Symlink("b", "a")]),
+        &ExtractLimits::default(),
+    );
+    assert!(matches!(result, Err(ArchiveError::UnsafeLink { .. })));
+
+    // but links to links are fine as long as they stay inside
+    let (dir, result) = unpack(
+        &tarball(&[
+            Entry::File("icons/scalable/app.svg", b"<svg/>"),
+            Entry::Symlink("icons/48/app.svg", "../scalable/app.svg"),
+            Entry::Symlink("icons/32/app.svg", "../48/app.svg"),
+        ]),
+        &ExtractLimits::default(),
+    );
+    result.unwrap();
+    assert_eq!(
+        fs::read(dir.path().join("out/icons/32/app.svg")).unwrap(),
+        b"<svg/>"
+    );
+}
+
+#[test]
+fn refuse_hard_links_to_symlinks() {
+    // the hard link would be a copy of the symlink, but three levels higher
+    let archive = tarball(&[
+        Entry::Symlink("x/y/z/l", "../../.."),
+        Entry::HardLink("h", "x/y/z/l"),
+        Entry::File("h/pwned", b"x"),
+    ]);
+
+    // deep enough that getting out still lands in the temporary directory
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("download.bin");
+    fs::write(&path, archive).unwrap();
+    let result = extract(
+        &path,
+        &dir.path().join("1/2/3/out"),
+        &ExtractLimits::default(),
+    );
+
+    assert!(
+        matches!(result, Err(ArchiveError::UnsafeLink { .. })),
+        "{result:?}"
+    );
+    assert!(!dir.path().join("1/pwned").exists());
+}
+
+#[test]
+fn refuse_to_write_through_links() {
+    // `link` itself is fine, but using it as a directory is not
+    let (_dir, result) = unpack(
+        &tarball(&[Entry::Symlink("link", "."), Entry::File("link/file", b"x")]),
+        &ExtractLimits::default(),
+    );
+    assert!(matches!(result, Err(ArchiveError::UnsafePath(_))));
+}
+
+#[test]
+fn refuse_archive_bombs() {
+    let bomb = gzip(&tarball(&[Entry::File(
+        "zeroes",
+        &vec![0; 4 * 1024 * 1024],
+    )]));
+
+    let (_dir, result) = unpack(&bomb, &ExtractLimits::default());
+    assert!(matches!(result, Err(ArchiveError::RatioTooHigh(_))));
+}
+
+#[test]
+fn enforce_the_limits() {
+    let tar = tarball(&[
+        Entry::File("a", b"aaaa"),
+        Entry::File("b", b"bbbb"),
+        Entry::File("c", b"cccc"),
+    ]);
+
+    let (_dir, result) = unpack(
+        &tar,
+        &ExtractLimits {
+            max_entries: 2,
+            ..Default::default()
+        },
+    );
+    assert!(matches!(result, Err(ArchiveError::TooManyEntries(2))));
+
+    let (_dir, result) = unpack(
+        &tar,
+        &ExtractLimits {
+            max_total_size: 10,
+            ..Default::default()
+        },
+    );
+    assert!(matches!(result, Err(ArchiveError::TooBig(10))));
+}
+
+#[test]
+fn refuse_things_that_arent_archives() {
+    let (_dir, result) = unpack(b"just some text", &ExtractLimits::default());
+    assert!(matches!(result, Err(ArchiveError::NotAnArchive)));
+}
+
+#[test]
+fn list_without_writing() {
+    let tar = gzip(&tarball(&[
+        Entry::Dir("Nordic/"),
+        Entry::File("Nordic/gtk-3.0/gtk.css", b"* {}"),
+        Entry::Symlink("Nordic/gtk-4.0", "gtk-3.0"),
+    ]));
+
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir.path().join("download.bin");
+    fs::write(&archive, tar).unwrap();
+
+    let entries = list(&archive, &ExtractLimits::default()).unwrap();
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|entry| (entry.path.to_str().unwrap(), entry.size, entry.is_dir))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Nordic", 0, true),
+            ("Nordic/gtk-3.0/gtk.css", 4, false),
+            ("Nordic/gtk-4.0", 0, false),
+        ]
+    );
+
+    assert_eq!(
+        fs::read_dir(dir.path()).unwrap().count(),
+        1,
+        "only the archive is there"
+    );
+
+    // the same checks still apply
+    fs::write(&archive, tarball(&[Entry::File("../evil", b"x")])).unwrap();
+    assert!(matches!(
+        list(&archive, &ExtractLimits::default()),
+        Err(ArchiveError::UnsafePath(_))
+    ));
+}
+
+#[test]
+fn extract_can_be_cancelled() {
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir.path().join("download.bin");
+    fs::write(&archive, gzip(&tarball(&[Entry::File("a", b"a")]))).unwrap();
+
+    let cancel = Cancellation::new();
+    cancel.cancel();
+
+    let result = extract_cancellable(
+        &archive,
+        &dir.path().join("out"),
+        &ExtractLimits::default(),
+        &cancel,
+    );
+    assert!(matches!(result, Err(ArchiveError::Cancelled)));
+    assert!(!dir.path().join("out/a").exists());
+}
@@ -0,0 +1,154 @@
+#![allow(unused)]
+use crate::config::{Config, ConfigError};
+use crate::installer::ConflictPolicy;
+use crate::paths::PathResolver;
+use crate::types::install_type::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+fn resolver_for(home: &Path) -> PathResolver {
+    PathResolver::with_vars(HashMap::from([(
+        "HOME".into(),
+        home.to_string_lossy().into_owned(),
+    )]))
+}
+
+#[test]
+fn no_config_file() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_for(home.path());
+
+    let config = Config::load(&resolver).unwrap();
+    assert_eq!(config, Config::default());
+    assert_eq!(
+        config.destination(&InstallType::Styling(Styling::Fonts), &resolver),
+        Ok(home.path().join(".local/share/fonts"))
+    );
+}
+
+#[test]
+fn overrides_from_file() {
+    let home = tempfile::tempdir().unwrap();
+    let shared = tempfile::tempdir().unwrap();
+    let resolver = resolver_for(home.path());
+
+    let path = Config::path(&resolver).unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(
+        &path,
+        format!(
+            "[destinations]\n\
+             fonts = \"{shared}/fonts\"\n\
+             gtk3_themes = \"$HOME/shared-themes\"\n\
+             [categories]\n\
+             styling = \"{shared}/styling\"\n",
+            shared = shared.path().display()
+        ),
+    )
+    .unwrap();
+
+    let config = Config::load(&resolver).unwrap();
+    let destination = |install_type| config.destination(&install_type, &resolver).unwrap();
+
+    // install types first...
+    assert_eq!(
+        destination(InstallType::Styling(Styling::Fonts)),
+        shared.path().join("fonts")
+    );
+    assert_eq!(
+        destination(InstallType::Styling(Styling::GTK3Themes)),
+        home.path().join("shared-themes")
+    );
+
+    // ...then categories...
+    assert_eq!(
+        destination(InstallType::Styling(Styling::Icons)),
+        shared.path().join("styling")
+    );
+
+    // ...then the usual place
+    assert_eq!(
+        destination(InstallType::QtGeneral(QtGeneral::KwinTabbox)),
+        home.path().join(".local/share/kwin/tabbox")
+    );
+}
+
+#[test]
+fn bad_overrides() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_for(home.path());
+    fs::write(home.path().join("a_file"), "").unwrap();
+
+    assert!(matches!(
+        Config::from_toml("[destinations]\nfontz = \"$HOME/fonts\"", &resolver),
+        Err(ConfigError::UnknownInstallType(key)) if key == "fontz"
+    ));
+    assert!(matches!(
+        Config::from_toml("[categories]\nstuff = \"$HOME/stuff\"", &resolver),
+        Err(ConfigError::UnknownCategory(key)) if key == "stuff"
+    ));
+    assert!(matches!(
+        Config::from_toml("[farts]\nfonts = \"$HOME/fonts\"", &resolver),
+        Err(ConfigError::UnknownKey(key)) if key == "farts"
+    ));
+    assert!(matches!(
+        Config::from_toml("[farts]", &resolver),
+        Err(ConfigError::UnknownKey(key)) if key == "farts"
+    ));
+    assert!(matches!(
+        Config::from_toml("farts = \"$HOME/fonts\"", &resolver),
+        Err(ConfigError::UnknownKey(key)) if key == "farts"
+    ));
+    assert!(matches!(
+        Config::from_toml("[destinations]\nfonts = 5", &resolver),
+        Err(ConfigError::NotAPath(key)) if key == "destinations.fonts"
+    ));
+    assert!(matches!(
+        Config::from_toml("[categories]\nicons = true", &resolver),
+        Err(ConfigError::NotAPath(key)) if key == "categories.icons"
+    ));
+    assert!(matches!(
+        Config::from_toml("[destinations]\nfonts = \"fonts\"", &resolver),
+        Err(ConfigError::RelativePath { .. })
+    ));
+    assert!(matches!(
+        Config::from_toml("[destinations]\nfonts = \"$HOME/a_file/fonts\"", &resolver),
+        Err(ConfigError::NotWritable { key, path })
+            if key == "fonts" && path == home.path().join("a_file/fonts")
+    ));
+    assert!(matches!(
+        Config::from_toml("[destinations", &resolver),
+        Err(ConfigError::InvalidToml(_))
+    ));
+}
+
+#[test]
+fn conflict_policy_from_file() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_for(home.path());
+
+    let config = Config::from_toml("[install]\nconflict_policy = \"keep-both\"", &resolver);
+    assert_eq!(config.unwrap().conflict_policy(), ConflictPolicy::KeepBoth);
+    assert_eq!(Config::default().conflict_policy(), ConflictPolicy::Backup);
+
+    assert!(matches!(
+        Config::from_toml("[install]\nconflict_policy = \"explode\"", &resolver),
+        Err(ConfigError::UnknownConflictPolicy(policy)) if policy == "explode"
+    ));
+    assert!(matches!(
+        Config::from_toml("[install]\npolicy = \"skip\"", &resolver),
+        Err(ConfigError::UnknownKey(key)) if key == "install.policy"
+    ));
+
+    // it isn't a path, so it shouldn't ask for one
+    assert!(matches!(
+        Config::from_toml("[install]\nconflict_policy = 1", &resolver),
+        Err(ConfigError::UnknownConflictPolicy(policy)) if policy == "1"
+    ));
+    assert!(matches!(
+        Config::from_toml("[install]\npolicy = 1", &resolver),
+        Err(ConfigError::UnknownKey(key)) if key == "install.policy"
+    ));
+}
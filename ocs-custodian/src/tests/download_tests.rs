@@ -0,0 +1,115 @@
+#![allow(unused)]
+use crate::handler::{DownloadError, DownloadOptions, DownloadProgress};
+use crate::parser::check_url;
+use crate::progress::Cancellation;
+use crate::tests::test_helpers::{link_to, serve};
+
+use std::time::Duration;
+
+#[test]
+fn download_with_progress() {
+    let body = b"totally an icon theme".repeat(10_000);
+    let server = serve(200, body.clone(), false);
+    let staging = tempfile::tempdir().unwrap();
+
+    let link = check_url(link_to(
+        &server,
+        "/files/icons.tar.gz",
+        Some("Nordic.tar.gz"),
+    ))
+    .unwrap();
+
+    let mut updates: Vec<DownloadProgress> = Vec::new();
+    let saved = link
+        .download(staging.path(), &DownloadOptions::default(), |progress| {
+            updates.push(progress)
+        })
+        .unwrap();
+
+    // the link's filename wins
+    assert_eq!(saved, staging.path().join("Nordic.tar.gz"));
+    assert_eq!(std::fs::read(&saved).unwrap(), body);
+
+    // we hear about every chunk, and how big the whole thing is
+    let last = updates.last().unwrap();
+    assert_eq!(last.downloaded, body.len() as u64);
+    assert!(updates
+        .iter()
+        .all(|update| update.total == Some(body.len() as u64)));
+    assert!(updates.len() > 2);
+
+    // no leftovers
+    assert_eq!(std::fs::read_dir(staging.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn download_filename_from_url() {
+    let server = serve(200, b"hi".to_vec(), false);
+    let staging = tempfile::tempdir().unwrap();
+
+    let link = check_url(link_to(&server, "/files/Some%20Icons.zip", None)).unwrap();
+    let saved = link
+        .download(staging.path(), &DownloadOptions::default(), |_| ())
+        .unwrap();
+
+    assert_eq!(saved, staging.path().join("Some Icons.zip"));
+}
+
+#[test]
+fn download_bad_filename() {
+    let link = check_url(link_to("http://127.0.0.1:9", "/a.zip", Some(".."))).unwrap();
+
+    assert!(matches!(
+        link.download_filename(),
+        Err(DownloadError::BadFilename(name)) if name == ".."
+    ));
+}
+
+#[test]
+fn download_http_error() {
+    let server = serve(404, b"not here".to_vec(), false);
+    let staging = tempfile::tempdir().unwrap();
+
+    let link = check_url(link_to(&server, "/gone.zip", None)).unwrap();
+    let result = link.download(staging.path(), &DownloadOptions::default(), |_| ());
+
+    assert!(matches!(
+        result,
+        Err(DownloadError::HttpStatus { status: 404, .. })
+    ));
+    assert_eq!(std::fs::read_dir(staging.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn download_timeout() {
+    let server = serve(200, Vec::new(), true);
+    let staging = tempfile::tempdir().unwrap();
+
+    let options = DownloadOptions {
+        read_timeout: Duration::from_millis(200),
+        ..Default::default()
+    };
+    let link = check_url(link_to(&server, "/slow.zip", None)).unwrap();
+
+    assert!(matches!(
+        link.download(staging.path(), &options, |_| ()),
+        Err(DownloadError::Transport { .. })
+    ));
+    assert_eq!(std::fs::read_dir(staging.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn download_can_be_cancelled() {
+    let server = serve(200, b"x".repeat(1_000_000), false);
+    let staging = tempfile::tempdir().unwrap();
+    let cancel = Cancellation::new();
+
+    let link = check_url(link_to(&server, "/big.zip", None)).unwrap();
+    let result =
+        link.download_cancellable(staging.path(), &DownloadOptions::default(), &cancel, |_| {
+            cancel.cancel()
+        });
+
+    assert!(matches!(result, Err(DownloadError::Cancelled)));
+    assert_eq!(std::fs::read_dir(staging.path()).unwrap().count(), 0);
+}
@@ -1,4 +1,7 @@
 #![allow(unused)]
+use crate::parser::check_url;
+use crate::paths::PathResolver;
+use crate::tests::test_helpers::{new_link, LinkParts};
 use crate::types::install_type::{
     self, PersonalMedia::*, QtGeneral::*, Styling::*, WMThemes::*, *,
 };
@@ -12,11 +15,22 @@ fn some_try_from_action() {
     assert!(PersonalMedia::try_from("farts").is_err());
 
     // styling
-    assert_eq!(Styling::try_from("xfwm4_themes"), Ok(Themes));
-    assert_eq!(Styling::try_from("openbox_themes"), Ok(Themes));
+    assert_eq!(Styling::try_from("xfwm4_themes"), Ok(Xfwm4Themes));
+    assert_eq!(Styling::try_from("openbox_themes"), Ok(OpenboxThemes));
     assert_eq!(Styling::try_from("themes"), Ok(Themes));
     assert_eq!(Styling::try_from("icons"), Ok(Icons));
     assert!(Styling::try_from("bigger farts").is_err());
+
+    // any category
+    assert_eq!(
+        InstallType::try_from("kwin_tabbox"),
+        Ok(InstallType::QtGeneral(KwinTabbox))
+    );
+    assert_eq!(
+        InstallType::try_from("nautilus_scripts"),
+        Ok(InstallType::AppSpecific(AppSpecific::NautiliusScripts))
+    );
+    assert!(InstallType::try_from("the biggest farts").is_err());
 }
 
 #[test]
@@ -31,3 +45,389 @@ fn install_paths_sheeeesh() {
         "$XDG_DATA_HOME/nautilus/scripts"
     )
 }
+
+#[test]
+fn registry_knows_every_alias() {
+    let registry = InstallType::registry();
+
+    // every alias should lead back to the type that lists it
+    for entry in &registry {
+        assert_eq!(InstallType::from_alias(entry.alias), Ok(entry.install_type));
+        assert_eq!(entry.category, entry.install_type.category());
+        assert_eq!(entry.destination, entry.install_type.get_install_path());
+    }
+
+    // no alias should be claimed twice
+    let mut aliases: Vec<_> = registry.iter().map(|entry| entry.alias).collect();
+    aliases.sort();
+    aliases.dedup();
+    assert_eq!(aliases.len(), registry.len());
+}
+
+#[test]
+fn registry_matches_the_csv() {
+    // The CSV is our human-readable copy of the table. Keep them in sync!
+    let csv = include_str!("../types/install_type.csv");
+
+    let mut rows = 0;
+    for line in csv.lines().skip(1).filter(|line| !line.is_empty()) {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim_matches('"')).collect();
+        let (category, aliases, location) = (fields[0], fields[2], fields[3]);
+
+        for alias in aliases.split(';') {
+            let install_type = InstallType::from_alias(alias).unwrap();
+            assert_eq!(install_type.category().to_string(), category);
+            assert_eq!(install_type.get_install_path(), location);
+            rows += 1;
+        }
+    }
+
+    assert_eq!(rows, InstallType::registry().len());
+}
+
+#[test]
+fn theme_flavours_check_their_layout() {
+    let themes = tempfile::tempdir().unwrap();
+    let nordic = themes.path().join("Nordic");
+    std::fs::create_dir_all(nordic.join("gtk-3.0")).unwrap();
+    std::fs::create_dir_all(nordic.join("xfwm4")).unwrap();
+
+    assert!(GTK3Themes.check_layout(&nordic).is_ok());
+    assert!(Xfwm4Themes.check_layout(&nordic).is_ok());
+    assert!(Themes.check_layout(&nordic).is_ok());
+    assert_eq!(
+        OpenboxThemes.check_layout(&nordic),
+        Err(InstallTypeError::UnexpectedLayout {
+            item: nordic.clone(),
+            expected: "a `openbox-3` directory".into(),
+        })
+    );
+
+    // kvantum themes are a different beast
+    assert!(KvantumThemes.check_layout(&nordic).is_err());
+    std::fs::write(nordic.join("Nordic.kvconfig"), "[%General]").unwrap();
+    assert!(KvantumThemes.check_layout(&nordic).is_ok());
+    assert_eq!(KvantumThemes.get_install_path(), "$XDG_CONFIG_HOME/Kvantum");
+}
+
+/// Every alias that OCS-URL and the stores use, with its category and
+/// destination. If you add an install type, add it here too!
+const EVERY_ALIAS: &[(&str, InstallCategory, &str)] = &[
+    ("bin", InstallCategory::PersonalMedia, "$HOME/.local/bin"),
+    ("books", InstallCategory::PersonalMedia, "$APP_DATA/books"),
+    ("comics", InstallCategory::PersonalMedia, "$APP_DATA/comics"),
+    (
+        "documents",
+        InstallCategory::PersonalMedia,
+        "$XDG_DOCUMENTS_DIR",
+    ),
+    (
+        "downloads",
+        InstallCategory::PersonalMedia,
+        "$XDG_DOWNLOAD_DIR",
+    ),
+    ("music", InstallCategory::PersonalMedia, "$XDG_MUSIC_DIR"),
+    (
+        "pictures",
+        InstallCategory::PersonalMedia,
+        "$XDG_PICTURES_DIR",
+    ),
+    ("videos", InstallCategory::PersonalMedia, "$XDG_VIDEOS_DIR"),
+    (
+        "wallpapers",
+        InstallCategory::PersonalMedia,
+        "$XDG_DATA_HOME/wallpapers",
+    ),
+    (
+        "color_schemes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/color-schemes",
+    ),
+    (
+        "plasma_color_schemes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/color-schemes",
+    ),
+    ("cursors", InstallCategory::Styling, "$XDG_DATA_HOME/icons"),
+    (
+        "emoticons",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/emoticons",
+    ),
+    ("fonts", InstallCategory::Styling, "$XDG_DATA_HOME/fonts"),
+    ("grub_themes", InstallCategory::Styling, "/boot/grub/themes"),
+    ("icons", InstallCategory::Styling, "$XDG_DATA_HOME/icons"),
+    (
+        "plymouth_themes",
+        InstallCategory::Styling,
+        "/usr/share/plymouth/themes",
+    ),
+    (
+        "sound_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/sounds",
+    ),
+    ("sounds", InstallCategory::Styling, "$XDG_DATA_HOME/sounds"),
+    ("themes", InstallCategory::Styling, "$XDG_DATA_HOME/themes"),
+    (
+        "cinnamon_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/themes",
+    ),
+    (
+        "gnome_shell_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/themes",
+    ),
+    (
+        "gtk2_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/themes",
+    ),
+    (
+        "gtk3_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/themes",
+    ),
+    (
+        "kvantum_themes",
+        InstallCategory::Styling,
+        "$XDG_CONFIG_HOME/Kvantum",
+    ),
+    (
+        "metacity_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/themes",
+    ),
+    (
+        "openbox_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/themes",
+    ),
+    (
+        "xfwm4_themes",
+        InstallCategory::Styling,
+        "$XDG_DATA_HOME/themes",
+    ),
+    (
+        "cairo_clock_themes",
+        InstallCategory::WMThemes,
+        "$HOME/.cairo-clock/themes",
+    ),
+    (
+        "cinnamon_applets",
+        InstallCategory::WMThemes,
+        "$XDG_DATA_HOME/cinnamon/applets",
+    ),
+    (
+        "cinnamon_desklets",
+        InstallCategory::WMThemes,
+        "$XDG_DATA_HOME/cinnamon/desklets",
+    ),
+    (
+        "cinnamon_extensions",
+        InstallCategory::WMThemes,
+        "$XDG_DATA_HOME/cinnamon/extensions",
+    ),
+    (
+        "emerald_themes",
+        InstallCategory::WMThemes,
+        "$HOME/.emerald/themes",
+    ),
+    (
+        "compiz_themes",
+        InstallCategory::WMThemes,
+        "$HOME/.emerald/themes",
+    ),
+    (
+        "beryl_themes",
+        InstallCategory::WMThemes,
+        "$HOME/.emerald/themes",
+    ),
+    (
+        "enlightenment_backgrounds",
+        InstallCategory::WMThemes,
+        "$HOME/.e/e/backgrounds",
+    ),
+    (
+        "enlightenment_themes",
+        InstallCategory::WMThemes,
+        "$HOME/.e/e/themes",
+    ),
+    (
+        "fluxbox_styles",
+        InstallCategory::WMThemes,
+        "$HOME/.fluxbox/styles",
+    ),
+    (
+        "gnome_shell_extensions",
+        InstallCategory::WMThemes,
+        "$XDG_DATA_HOME/gnome-shell/extensions",
+    ),
+    (
+        "icewm_themes",
+        InstallCategory::WMThemes,
+        "$HOME/.icewm/themes",
+    ),
+    (
+        "pekwm_themes",
+        InstallCategory::WMThemes,
+        "$HOME/.pekwm/themes",
+    ),
+    (
+        "amarok_scripts",
+        InstallCategory::QtGeneral,
+        "$KDEHOME/share/apps/amarok/scripts",
+    ),
+    (
+        "aurorae_themes",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/aurorae/themes",
+    ),
+    (
+        "dekorator_themes",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/deKorator/themes",
+    ),
+    (
+        "kwin_effects",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/kwin/effects",
+    ),
+    (
+        "kwin_scripts",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/kwin/scripts",
+    ),
+    (
+        "kwin_tabbox",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/kwin/tabbox",
+    ),
+    (
+        "latte_layouts",
+        InstallCategory::QtGeneral,
+        "$XDG_CONFIG_HOME/latte",
+    ),
+    (
+        "plasma_desktopthemes",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/plasma/desktoptheme",
+    ),
+    (
+        "plasma5_desktopthemes",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/plasma/desktoptheme",
+    ),
+    (
+        "plasma_look_and_feel",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/plasma/look-and-feel",
+    ),
+    (
+        "plasma5_look_and_feel",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/plasma/look-and-feel",
+    ),
+    (
+        "plasma_plasmoids",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/plasma/plasmoids",
+    ),
+    (
+        "plasma4_plasmoids",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/plasma/plasmoids",
+    ),
+    (
+        "plasma5_plasmoids",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/plasma/plasmoids",
+    ),
+    (
+        "qtcurve",
+        InstallCategory::QtGeneral,
+        "$XDG_DATA_HOME/QtCurve",
+    ),
+    (
+        "sddm_themes",
+        InstallCategory::QtGeneral,
+        "/usr/share/sddm/themes",
+    ),
+    (
+        "yakuake_skins",
+        InstallCategory::QtGeneral,
+        "$KDEHOME/share/apps/yakuake/skins",
+    ),
+    (
+        "caja_scripts",
+        InstallCategory::AppSpecific,
+        "$XDG_CONFIG_HOME/caja/scripts",
+    ),
+    (
+        "conky_themes",
+        InstallCategory::AppSpecific,
+        "$XDG_CONFIG_HOME/conky",
+    ),
+    (
+        "conky",
+        InstallCategory::AppSpecific,
+        "$XDG_CONFIG_HOME/conky",
+    ),
+    (
+        "dolphin_service_menus",
+        InstallCategory::AppSpecific,
+        "$XDG_DATA_HOME/kio/servicemenus",
+    ),
+    (
+        "kde_service_menus",
+        InstallCategory::AppSpecific,
+        "$XDG_DATA_HOME/kio/servicemenus",
+    ),
+    (
+        "nautilus_scripts",
+        InstallCategory::AppSpecific,
+        "$XDG_DATA_HOME/nautilus/scripts",
+    ),
+    (
+        "nemo_actions",
+        InstallCategory::AppSpecific,
+        "$XDG_DATA_HOME/nemo/actions",
+    ),
+    (
+        "nemo_scripts",
+        InstallCategory::AppSpecific,
+        "$XDG_DATA_HOME/nemo/scripts",
+    ),
+];
+
+#[test]
+fn every_alias_parses_and_resolves() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = PathResolver::with_vars(std::collections::HashMap::from([(
+        "HOME".into(),
+        home.path().to_string_lossy().into_owned(),
+    )]));
+
+    for (alias, category, destination) in EVERY_ALIAS {
+        let parsed = check_url(new_link(LinkParts::InstallType, alias))
+            .unwrap_or_else(|e| panic!("`{alias}` didn't parse: {e}"));
+
+        assert_eq!(parsed.install_type.category(), *category, "{alias}");
+        assert_eq!(
+            parsed.install_type.get_install_path(),
+            *destination,
+            "{alias}"
+        );
+
+        let path = resolver.install_path(&parsed.install_type).unwrap();
+        assert!(path.is_absolute(), "{alias}");
+        assert_eq!(
+            path.starts_with(home.path()),
+            !parsed.install_type.is_system_wide(),
+            "{alias}"
+        );
+    }
+
+    assert_eq!(EVERY_ALIAS.len(), InstallType::registry().len());
+}
@@ -0,0 +1,474 @@
+#![allow(unused)]
+use crate::installer::{
+    Conflict, ConflictPolicy, InstallError, InstallReport, Installer, UninstallError,
+};
+use crate::manifest::Manifest;
+use crate::parser::check_url;
+use crate::progress::{Cancellation, InstallEvent, InstallStage};
+use crate::tests::test_helpers::{installer_for, link_to, serve, tar_gz};
+use crate::types::install_type::*;
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn install_a_file() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"a lovely cursor".to_vec(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+    let report = installer.install(&link, |_| ()).unwrap();
+
+    let icons = home.path().join(".local/share/icons");
+    assert_eq!(report.install_type, InstallType::Styling(Styling::Icons));
+    assert_eq!(report.destination, icons);
+    assert_eq!(report.items, vec![icons.join("cursor.png")]);
+    assert_eq!(report.files, vec![icons.join("cursor.png")]);
+    assert_eq!(
+        fs::read(icons.join("cursor.png")).unwrap(),
+        b"a lovely cursor"
+    );
+
+    // nothing is left in staging
+    let staging = home.path().join(".local/share/amizade/staging");
+    assert_eq!(fs::read_dir(staging).unwrap().count(), 0);
+}
+
+#[test]
+fn install_replaces_the_old_version() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"new".to_vec(), false);
+    let mut installer = installer_for(home.path());
+    installer.conflict_policy = ConflictPolicy::Overwrite;
+
+    let icons = home.path().join(".local/share/icons");
+    fs::create_dir_all(&icons).unwrap();
+    fs::write(icons.join("cursor.png"), "old").unwrap();
+
+    let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+    installer.install(&link, |_| ()).unwrap();
+
+    assert_eq!(fs::read(icons.join("cursor.png")).unwrap(), b"new");
+    // the old version and staging area are cleaned up
+    assert_eq!(fs::read_dir(&icons).unwrap().count(), 1);
+}
+
+#[test]
+fn install_resolves_legacy_duplicates() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"new".to_vec(), false);
+    let mut installer = installer_for(home.path());
+    let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+
+    let legacy = home.path().join(".icons/cursor.png");
+    let installed = home.path().join(".local/share/icons/cursor.png");
+    let reset = || {
+        let _ = fs::remove_dir_all(home.path().join(".icons"));
+        let _ = fs::remove_dir_all(home.path().join(".local/share/icons"));
+        fs::create_dir_all(home.path().join(".icons")).unwrap();
+        fs::write(&legacy, "old").unwrap();
+    };
+
+    reset();
+    installer.conflict_policy = ConflictPolicy::Skip;
+    let report = installer.install(&link, |_| ()).unwrap();
+    assert_eq!(report.conflicts[0].existing, legacy);
+    assert!(report.items.is_empty());
+    assert!(!installed.exists());
+
+    reset();
+    installer.conflict_policy = ConflictPolicy::Overwrite;
+    installer.install(&link, |_| ()).unwrap();
+    assert!(!legacy.exists());
+    assert_eq!(fs::read(&installed).unwrap(), b"new");
+
+    reset();
+    installer.conflict_policy = ConflictPolicy::KeepBoth;
+    let report = installer.install(&link, |_| ()).unwrap();
+    assert_eq!(report.conflicts[0].moved_to, Some(installed.clone()));
+    assert_eq!(fs::read(&legacy).unwrap(), b"old");
+    assert_eq!(fs::read(&installed).unwrap(), b"new");
+
+    reset();
+    installer.conflict_policy = ConflictPolicy::Backup;
+    let report = installer.install(&link, |_| ()).unwrap();
+    let backup = home.path().join(".icons/cursor.png.bak");
+    assert_eq!(report.conflicts[0].moved_to, Some(backup.clone()));
+    assert_eq!(fs::read(backup).unwrap(), b"old");
+    assert_eq!(fs::read(&installed).unwrap(), b"new");
+}
+
+#[test]
+fn install_into_the_legacy_location() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"new".to_vec(), false);
+    let mut installer = installer_for(home.path());
+    installer.conflict_policy = ConflictPolicy::Overwrite;
+
+    let legacy = home.path().join(".icons");
+    fs::create_dir_all(&legacy).unwrap();
+    installer
+        .config
+        .set_type_override("icons", &legacy.to_string_lossy(), &installer.resolver)
+        .unwrap();
+
+    // reinstalling is just a conflict like any other
+    let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+    installer.install(&link, |_| ()).unwrap();
+    let report = installer.install(&link, |_| ()).unwrap();
+
+    assert_eq!(report.items, vec![legacy.join("cursor.png")]);
+    assert_eq!(report.conflicts.len(), 1);
+}
+
+#[test]
+fn install_uses_config_overrides() {
+    let home = tempfile::tempdir().unwrap();
+    let shared = tempfile::tempdir().unwrap();
+    let server = serve(200, b"icons".to_vec(), false);
+    let mut installer = installer_for(home.path());
+
+    installer
+        .config
+        .set_type_override(
+            "icons",
+            &shared.path().to_string_lossy(),
+            &installer.resolver,
+        )
+        .unwrap();
+
+    let link = check_url(link_to(&server, "/dl/theme.png", None)).unwrap();
+    let report = installer.install(&link, |_| ()).unwrap();
+
+    assert_eq!(report.destination, shared.path());
+    assert!(shared.path().join("theme.png").exists());
+}
+
+#[test]
+fn system_wide_types_need_root() {
+    let home = tempfile::tempdir().unwrap();
+    let grub = tempfile::tempdir().unwrap();
+    // the download is never even tried
+    let server = serve(404, Vec::new(), false);
+    let mut installer = installer_for(home.path());
+
+    let link = link_to(&server, "/dl/theme.png", None).replace("type=icons", "type=grub_themes");
+    let link = check_url(link).unwrap();
+    let needs_root = |e| {
+        matches!(
+            e,
+            InstallError::NeedsRoot(InstallType::Styling(Styling::GRUBThemes))
+        )
+    };
+    assert!(needs_root(installer.install(&link, |_| ()).unwrap_err()));
+    assert!(needs_root(installer.plan(&link, |_| ()).unwrap_err()));
+
+    // somewhere else is fine
+    installer
+        .config
+        .set_type_override(
+            "grub_themes",
+            &grub.path().to_string_lossy(),
+            &installer.resolver,
+        )
+        .unwrap();
+    assert!(matches!(
+        installer.install(&link, |_| ()),
+        Err(InstallError::DownloadError(_))
+    ));
+}
+
+#[test]
+fn failed_install_leaves_nothing_behind() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(404, Vec::new(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/gone.png", None)).unwrap();
+    assert!(matches!(
+        installer.install(&link, |_| ()),
+        Err(InstallError::DownloadError(_))
+    ));
+    assert!(!home.path().join(".local/share/icons").exists());
+}
+
+#[test]
+fn install_unpacks_archives() {
+    let home = tempfile::tempdir().unwrap();
+    let archive = tar_gz(&[
+        ("Papirus/index.theme", b"[Icon Theme]"),
+        ("Papirus/16x16/a.svg", b"<svg/>"),
+    ]);
+    let server = serve(200, archive, false);
+    let installer = installer_for(home.path());
+
+    // the name says nothing about what's inside
+    let link = check_url(link_to(&server, "/dl/download", None)).unwrap();
+    let report = installer.install(&link, |_| ()).unwrap();
+
+    let icons = home.path().join(".local/share/icons");
+    assert_eq!(report.items, vec![icons.join("Papirus")]);
+    assert_eq!(
+        report.files,
+        vec![
+            icons.join("Papirus/16x16/a.svg"),
+            icons.join("Papirus/index.theme")
+        ]
+    );
+    assert!(!icons.join("download").exists());
+}
+
+#[test]
+fn install_reports_its_stages() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, tar_gz(&[("Papirus/index.theme", b"")]), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let mut stages = Vec::new();
+    let mut downloads = 0;
+    installer
+        .install_with(&link, &Cancellation::new(), |event| match event {
+            InstallEvent::Stage(stage) => stages.push(stage),
+            InstallEvent::Download(_) => downloads += 1,
+        })
+        .unwrap();
+
+    assert_eq!(
+        stages,
+        vec![
+            InstallStage::Downloading,
+            InstallStage::Extracting,
+            InstallStage::Placing,
+            InstallStage::Finishing,
+        ]
+    );
+    assert!(downloads > 0);
+}
+
+#[test]
+fn cancelled_install_leaves_nothing_behind() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, tar_gz(&[("Papirus/index.theme", b"")]), false);
+    let installer = installer_for(home.path());
+    let cancel = Cancellation::new();
+
+    // cancel as soon as the archive is downloaded
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let result = installer.install_with(&link, &cancel, |event| {
+        if event == InstallEvent::Stage(InstallStage::Extracting) {
+            cancel.cancel();
+        }
+    });
+
+    assert!(matches!(result, Err(InstallError::Cancelled)));
+    assert!(!home.path().join(".local/share/icons/Papirus").exists());
+    let staging = installer.resolver.app_data().unwrap().join("staging");
+    assert_eq!(fs::read_dir(staging).unwrap().count(), 0);
+    assert!(Manifest::load(&installer.resolver)
+        .unwrap()
+        .items()
+        .is_empty());
+}
+
+#[test]
+fn download_links_keep_archives_packed() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, tar_gz(&[("a", b"a")]), false);
+    let installer = installer_for(home.path());
+
+    let link = link_to(&server, "/dl/icons.tar.gz", None).replace("install", "download");
+    let report = installer
+        .install(&check_url(link).unwrap(), |_| ())
+        .unwrap();
+
+    let icons = home.path().join(".local/share/icons");
+    assert_eq!(report.items, vec![icons.join("icons.tar.gz")]);
+}
+
+#[test]
+fn install_checks_the_layout() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, tar_gz(&[("Nordic/index.theme", b"")]), false);
+    let installer = installer_for(home.path());
+
+    let link = link_to(&server, "/dl/nordic.tar.gz", None).replace("icons", "gtk3_themes");
+    assert!(matches!(
+        installer.install(&check_url(link).unwrap(), |_| ()),
+        Err(InstallError::InstallTypeError(
+            InstallTypeError::UnexpectedLayout { .. }
+        ))
+    ));
+    assert!(!home.path().join(".local/share/themes/Nordic").exists());
+}
+
+/// Installs a small icon theme, returning its id.
+fn install_papirus(installer: &Installer) -> u64 {
+    let archive = tar_gz(&[
+        ("Papirus/index.theme", b"[Icon Theme]"),
+        ("Papirus/16x16/a.svg", b"<svg/>"),
+    ]);
+    let server = serve(200, archive, false);
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    installer.install(&link, |_| ()).unwrap();
+
+    Manifest::load(&installer.resolver).unwrap().items()[0].id
+}
+
+#[test]
+fn uninstall_removes_what_was_installed() {
+    let home = tempfile::tempdir().unwrap();
+    let installer = installer_for(home.path());
+    let icons = home.path().join(".local/share/icons");
+    let id = install_papirus(&installer);
+
+    let report = installer.uninstall(id, false).unwrap();
+
+    assert_eq!(report.removed.len(), 2);
+    assert!(report.kept.is_empty());
+    assert!(!icons.join("Papirus").exists());
+    // the destination itself stays
+    assert!(icons.exists());
+    assert!(Manifest::load(&installer.resolver)
+        .unwrap()
+        .items()
+        .is_empty());
+}
+
+#[test]
+fn uninstall_keeps_other_files() {
+    let home = tempfile::tempdir().unwrap();
+    let installer = installer_for(home.path());
+    let id = install_papirus(&installer);
+
+    let papirus = home.path().join(".local/share/icons/Papirus");
+    fs::write(papirus.join("16x16/mine.svg"), "").unwrap();
+
+    let report = installer.uninstall(id, false).unwrap();
+
+    assert_eq!(report.kept, vec![papirus.clone()]);
+    assert!(papirus.join("16x16/mine.svg").exists());
+    assert!(!papirus.join("index.theme").exists());
+}
+
+#[test]
+fn uninstall_refuses_modified_files() {
+    let home = tempfile::tempdir().unwrap();
+    let installer = installer_for(home.path());
+    let id = install_papirus(&installer);
+
+    let papirus = home.path().join(".local/share/icons/Papirus");
+    fs::write(papirus.join("index.theme"), "[Icon Theme]\nName=Mine").unwrap();
+
+    assert!(matches!(
+        installer.uninstall(id, false),
+        Err(UninstallError::ModifiedSinceInstall(files)) if files == vec![papirus.join("index.theme")]
+    ));
+    assert!(papirus.join("16x16/a.svg").exists());
+
+    installer.uninstall(id, true).unwrap();
+    assert!(!papirus.exists());
+}
+
+#[test]
+fn uninstall_unknown_items() {
+    let home = tempfile::tempdir().unwrap();
+    let installer = installer_for(home.path());
+
+    assert!(matches!(
+        installer.uninstall(7, false),
+        Err(UninstallError::NotInstalled(7))
+    ));
+}
+
+/// Installs `cursor.png` over an old one, with the given policy.
+fn install_over_old_cursor(home: &Path, policy: ConflictPolicy) -> InstallReport {
+    let server = serve(200, b"new".to_vec(), false);
+    let mut installer = installer_for(home);
+    installer.conflict_policy = policy;
+
+    let icons = home.join(".local/share/icons");
+    fs::create_dir_all(&icons).unwrap();
+    fs::write(icons.join("cursor.png"), "old").unwrap();
+
+    let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+    installer.install(&link, |_| ()).unwrap()
+}
+
+#[test]
+fn conflicts_can_be_skipped() {
+    let home = tempfile::tempdir().unwrap();
+    let report = install_over_old_cursor(home.path(), ConflictPolicy::Skip);
+
+    let icons = home.path().join(".local/share/icons");
+    assert!(report.items.is_empty());
+    assert_eq!(
+        report.conflicts,
+        vec![Conflict {
+            existing: icons.join("cursor.png"),
+            policy: ConflictPolicy::Skip,
+            moved_to: None,
+        }]
+    );
+    assert_eq!(fs::read(icons.join("cursor.png")).unwrap(), b"old");
+    assert!(Manifest::load(&installer_for(home.path()).resolver)
+        .unwrap()
+        .items()
+        .is_empty());
+}
+
+#[test]
+fn conflicts_can_be_overwritten() {
+    let home = tempfile::tempdir().unwrap();
+    let report = install_over_old_cursor(home.path(), ConflictPolicy::Overwrite);
+
+    let icons = home.path().join(".local/share/icons");
+    assert_eq!(report.conflicts[0].policy, ConflictPolicy::Overwrite);
+    assert_eq!(fs::read(icons.join("cursor.png")).unwrap(), b"new");
+}
+
+#[test]
+fn conflicts_can_keep_both() {
+    let home = tempfile::tempdir().unwrap();
+    let report = install_over_old_cursor(home.path(), ConflictPolicy::KeepBoth);
+
+    let icons = home.path().join(".local/share/icons");
+    assert_eq!(report.items, vec![icons.join("cursor-2.png")]);
+    assert_eq!(
+        report.conflicts[0].moved_to,
+        Some(icons.join("cursor-2.png"))
+    );
+    assert_eq!(fs::read(icons.join("cursor.png")).unwrap(), b"old");
+    assert_eq!(fs::read(icons.join("cursor-2.png")).unwrap(), b"new");
+}
+
+#[test]
+fn conflicts_can_be_backed_up() {
+    let home = tempfile::tempdir().unwrap();
+    let report = install_over_old_cursor(home.path(), ConflictPolicy::Backup);
+
+    let icons = home.path().join(".local/share/icons");
+    assert_eq!(report.items, vec![icons.join("cursor.png")]);
+    assert_eq!(
+        report.conflicts[0].moved_to,
+        Some(icons.join("cursor.png.bak"))
+    );
+    assert_eq!(fs::read(icons.join("cursor.png.bak")).unwrap(), b"old");
+    assert_eq!(fs::read(icons.join("cursor.png")).unwrap(), b"new");
+}
+
+#[test]
+fn conflict_policies_have_names() {
+    for policy in ConflictPolicy::ALL {
+        assert_eq!(
+            ConflictPolicy::try_from(policy.to_string().as_str()),
+            Ok(policy)
+        );
+    }
+    assert_eq!(
+        ConflictPolicy::try_from("keep_both"),
+        Ok(ConflictPolicy::KeepBoth)
+    );
+    assert!(ConflictPolicy::try_from("explode").is_err());
+}
@@ -0,0 +1,102 @@
+#![allow(unused)]
+use crate::handler::{JsonError, JSON_SCHEMA_VERSION};
+use crate::parser::check_url;
+use crate::plan::InstallPlan;
+use crate::tests::test_helpers::{installer_for, link_to, new_link, serve, LinkParts};
+use crate::types::ParsedOcsUrl;
+
+#[test]
+fn json_round_trip() {
+    let link = check_url(new_link(LinkParts::NoChange, "")).unwrap();
+    let json = link.to_json().unwrap();
+
+    assert_eq!(ParsedOcsUrl::from_json(&json).unwrap(), link);
+}
+
+#[test]
+fn json_follows_the_schema() {
+    let link = check_url(new_link(LinkParts::Scheme, "xdg")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&link.to_json().unwrap()).unwrap();
+
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "ocs_url": link.ocs_url.as_str(),
+            "scheme": "xdg",
+            "command": "install",
+            "download_url": "https://fake.download/location.png",
+            "install_type": { "category": "QtGeneral", "kind": "PlasmaLookAndFeel" },
+            "install_type_alias": "plasma_look_and_feel",
+            "filename": "location55.png",
+        })
+    );
+}
+
+#[test]
+fn json_from_the_future() {
+    let link = check_url(new_link(LinkParts::NoChange, "")).unwrap();
+    let mut json: serde_json::Value = serde_json::from_str(&link.to_json().unwrap()).unwrap();
+
+    json["schema_version"] = 9000.into();
+    assert!(matches!(
+        ParsedOcsUrl::from_json(&json.to_string()),
+        Err(JsonError::UnsupportedSchemaVersion(Some(9000)))
+    ));
+
+    json.as_object_mut().unwrap().remove("schema_version");
+    assert!(matches!(
+        ParsedOcsUrl::from_json(&json.to_string()),
+        Err(JsonError::UnsupportedSchemaVersion(None))
+    ));
+}
+
+#[test]
+fn json_has_to_match_its_link() {
+    let link = check_url(new_link(LinkParts::NoChange, "")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&link.to_json().unwrap()).unwrap();
+
+    let tampered = [
+        (
+            "download_url",
+            serde_json::json!("https://evil.example/location.png"),
+        ),
+        (
+            "install_type",
+            serde_json::json!({ "category": "Styling", "kind": "Fonts" }),
+        ),
+        ("install_type_alias", serde_json::json!("fonts")),
+        ("filename", serde_json::json!("../../.bashrc")),
+    ];
+    for (field, value) in tampered {
+        let mut json = json.clone();
+        json[field] = value;
+
+        let error = ParsedOcsUrl::from_json(&json.to_string()).unwrap_err();
+        assert!(
+            error.to_string().contains("don't match its OCS URL"),
+            "{field}: {error}"
+        );
+    }
+
+    // the link itself has to be good too
+    let mut json = json;
+    json["ocs_url"] = "ocs://install?url=https%3A%2F%2Fexample.com&type=farts".into();
+    assert!(ParsedOcsUrl::from_json(&json.to_string()).is_err());
+}
+
+#[test]
+fn install_plans_serialize() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"a lovely cursor".to_vec(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+    let plan = installer.plan(&link, |_| ()).unwrap();
+    let json = serde_json::to_value(&plan).unwrap();
+
+    assert_eq!(json["conflict_policy"], "backup");
+    assert_eq!(json["files"][0]["action"], "create");
+    assert_eq!(json["download_size"], 15);
+    assert_eq!(serde_json::from_value::<InstallPlan>(json).unwrap(), plan);
+}
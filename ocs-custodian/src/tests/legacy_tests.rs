@@ -0,0 +1,104 @@
+#![allow(unused)]
+use crate::legacy::{find_legacy_item, migrate};
+use crate::paths::PathResolver;
+use crate::types::install_type::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+fn resolver_for(home: &Path) -> PathResolver {
+    PathResolver::with_vars(HashMap::from([(
+        "HOME".into(),
+        home.to_string_lossy().into_owned(),
+    )]))
+}
+
+#[test]
+fn finds_legacy_items() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_for(home.path());
+    let xdg = home.path().join(".local/share/themes");
+
+    fs::create_dir_all(home.path().join(".themes/Nordic/gtk-3.0")).unwrap();
+
+    assert_eq!(
+        find_legacy_item(&Styling::Themes, &resolver, &xdg, "Nordic").unwrap(),
+        Some(home.path().join(".themes/Nordic"))
+    );
+    assert_eq!(
+        find_legacy_item(&Styling::Themes, &resolver, &xdg, "Dracula").unwrap(),
+        None
+    );
+
+    // these never had a legacy location
+    assert_eq!(
+        find_legacy_item(&Styling::Emoticons, &resolver, &xdg, "Nordic").unwrap(),
+        None
+    );
+
+    // or it's where things are going anyway
+    assert_eq!(
+        find_legacy_item(
+            &Styling::Themes,
+            &resolver,
+            &home.path().join(".themes"),
+            "Nordic"
+        )
+        .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn linked_legacy_dir_isnt_a_duplicate() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_for(home.path());
+    let xdg = home.path().join(".local/share/themes");
+
+    fs::create_dir_all(home.path().join(".local/share/themes/Nordic")).unwrap();
+    std::os::unix::fs::symlink(
+        home.path().join(".local/share/themes"),
+        home.path().join(".themes"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        find_legacy_item(&Styling::Themes, &resolver, &xdg, "Nordic").unwrap(),
+        None
+    );
+}
+
+#[test]
+fn migrates_legacy_items() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_for(home.path());
+
+    fs::create_dir_all(home.path().join(".fonts/Inter")).unwrap();
+    fs::write(home.path().join(".fonts/Inter/Inter.ttf"), "font").unwrap();
+    fs::write(home.path().join(".fonts/Comic.ttf"), "old").unwrap();
+    fs::create_dir_all(home.path().join(".local/share/fonts")).unwrap();
+    fs::write(home.path().join(".local/share/fonts/Comic.ttf"), "new").unwrap();
+
+    let report = migrate(&Styling::Fonts, &resolver).unwrap();
+
+    assert_eq!(
+        report.moved,
+        vec![(
+            home.path().join(".fonts/Inter"),
+            home.path().join(".local/share/fonts/Inter")
+        )]
+    );
+    assert_eq!(report.skipped, vec![home.path().join(".fonts/Comic.ttf")]);
+
+    // the clash is left alone on both sides
+    assert_eq!(
+        fs::read_to_string(home.path().join(".local/share/fonts/Inter/Inter.ttf")).unwrap(),
+        "font"
+    );
+    assert_eq!(
+        fs::read_to_string(home.path().join(".local/share/fonts/Comic.ttf")).unwrap(),
+        "new"
+    );
+    assert!(home.path().join(".fonts/Comic.ttf").exists());
+}
@@ -0,0 +1,158 @@
+#![allow(unused)]
+use crate::installer::InstallReport;
+use crate::manifest::{hash_file, Manifest, ManifestError};
+use crate::parser::check_url;
+use crate::tests::test_helpers::{installer_for, link_to, serve};
+use crate::types::install_type::*;
+
+use std::fs;
+
+#[test]
+fn installs_are_recorded() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"a lovely cursor".to_vec(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+    installer.install(&link, |_| ()).unwrap();
+
+    let manifest = Manifest::load(&installer.resolver).unwrap();
+    let icons = home.path().join(".local/share/icons");
+    let item = &manifest.items()[0];
+
+    assert_eq!(manifest.items().len(), 1);
+    assert_eq!(item.link, link);
+    assert_eq!(item.install_type, InstallType::Styling(Styling::Icons));
+    assert_eq!(item.destination, icons);
+    assert_eq!(item.items, vec![icons.join("cursor.png")]);
+    assert_eq!(item.source_url, link.download_url);
+    assert_eq!(item.name(), "cursor.png");
+    assert_eq!(item.files[0].size, 15);
+    assert_eq!(
+        item.files[0].sha256,
+        hash_file(&icons.join("cursor.png")).unwrap().0
+    );
+
+    assert_eq!(manifest.get(item.id), Some(item));
+    assert_eq!(manifest.owner_of(&icons.join("cursor.png")), Some(item));
+    assert_eq!(manifest.in_category(InstallCategory::Styling).count(), 1);
+    assert_eq!(
+        manifest
+            .of_type(InstallType::Styling(Styling::Fonts))
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn reinstalls_update_the_same_item() {
+    let home = tempfile::tempdir().unwrap();
+    let installer = installer_for(home.path());
+
+    for body in ["old", "new"] {
+        let server = serve(200, body.into(), false);
+        let link = check_url(link_to(&server, "/dl/cursor.png", None)).unwrap();
+        installer.install(&link, |_| ()).unwrap();
+    }
+
+    let manifest = Manifest::load(&installer.resolver).unwrap();
+    let item = &manifest.items()[0];
+    assert_eq!(manifest.items().len(), 1);
+    assert_eq!(item.files[0].size, 3);
+    assert!(item.installed_at <= item.updated_at);
+}
+
+#[test]
+fn manifests_round_trip() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"font".to_vec(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/a%20font.ttf", Some("font.ttf"))).unwrap();
+    installer.install(&link, |_| ()).unwrap();
+
+    let mut manifest = Manifest::load(&installer.resolver).unwrap();
+    manifest.save().unwrap();
+    assert_eq!(Manifest::load(&installer.resolver).unwrap(), manifest);
+
+    let id = manifest.items()[0].id;
+    assert!(manifest.remove(id).is_some());
+    manifest.save().unwrap();
+    assert!(Manifest::load(&installer.resolver)
+        .unwrap()
+        .items()
+        .is_empty());
+}
+
+#[test]
+fn missing_manifests_are_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = Manifest::open(&dir.path().join("installed.json")).unwrap();
+
+    assert!(manifest.items().is_empty());
+}
+
+#[test]
+fn refuse_newer_or_damaged_manifests() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("installed.json");
+
+    fs::write(&path, r#"{"version": 99, "items": []}"#).unwrap();
+    assert!(matches!(
+        Manifest::open(&path),
+        Err(ManifestError::UnsupportedVersion(99))
+    ));
+
+    fs::write(&path, r#"{"version": 1, "items": [{"id": 1}]}"#).unwrap();
+    assert!(matches!(
+        Manifest::open(&path),
+        Err(ManifestError::Corrupt { .. })
+    ));
+}
+
+#[test]
+fn installs_replace_every_item_they_overlap() {
+    let home = tempfile::tempdir().unwrap();
+    let installer = installer_for(home.path());
+
+    for name in ["a.png", "b.png"] {
+        let server = serve(200, name.into(), false);
+        let link = check_url(link_to(&server, &format!("/dl/{name}"), None)).unwrap();
+        installer.install(&link, |_| ()).unwrap();
+    }
+
+    let icons = home.path().join(".local/share/icons");
+    let mut manifest = Manifest::load(&installer.resolver).unwrap();
+    let first = manifest.items()[0].clone();
+    let both = InstallReport {
+        install_type: first.install_type,
+        destination: icons.clone(),
+        items: vec![icons.join("a.png"), icons.join("b.png")],
+        files: vec![icons.join("a.png"), icons.join("b.png")],
+        conflicts: Vec::new(),
+        warnings: Vec::new(),
+    };
+    manifest.record(&first.link, &both).unwrap();
+
+    assert_eq!(manifest.items().len(), 1);
+    assert_eq!(manifest.items()[0].id, first.id);
+    assert_eq!(manifest.items()[0].installed_at, first.installed_at);
+    assert_eq!(
+        manifest.owner_of(&icons.join("b.png")).unwrap().id,
+        first.id
+    );
+}
+
+#[test]
+fn manifests_can_only_be_locked_once() {
+    let home = tempfile::tempdir().unwrap();
+    let installer = installer_for(home.path());
+    let path = Manifest::path(&installer.resolver).unwrap();
+
+    let lock = Manifest::lock(&installer.resolver).unwrap();
+    let other = fs::File::open(path.with_extension("json.lock")).unwrap();
+    assert!(other.try_lock().is_err());
+
+    drop(lock);
+    assert!(other.try_lock().is_ok());
+}
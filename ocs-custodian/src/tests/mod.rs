@@ -1,3 +1,15 @@
+mod archive_tests;
+mod config_tests;
+mod download_tests;
 mod install_type_tests;
+mod installer_tests;
+#[cfg(feature = "serde")]
+mod json_tests;
+mod legacy_tests;
+mod manifest_tests;
+mod ocs_api_tests;
 mod parser_tests;
+mod paths_tests;
+mod plan_tests;
 mod test_helpers;
+mod transaction_tests;
@@ -0,0 +1,207 @@
+#![allow(unused)]
+use crate::ocs_api::{content_id_from_page, is_store_download, OcsApiError, OcsClient};
+use crate::parser::check_url;
+use crate::tests::test_helpers::{link_to, serve};
+
+use url::Url;
+
+/// What Pling sends back for `content/data/{id}?format=json`, give or take.
+const PLING_RESPONSE: &str = r#"{
+    "status": "ok",
+    "statuscode": 100,
+    "message": "",
+    "totalitems": 1,
+    "data": [{
+        "details": "full",
+        "id": 1267246,
+        "name": "Nordic",
+        "version": "2.2.0",
+        "personid": "eliverlara",
+        "license": "GPLv3",
+        "description": "Dark Gtk3.20+ theme created using the awesome Nord color pallete.",
+        "previewpic1": "https://images.pling.com/img/nordic-1.png",
+        "previewpic2": "https://images.pling.com/img/nordic-2.png",
+        "smallpreviewpic1": "https://images.pling.com/cache/nordic-1.png",
+        "downloadname1": "Nordic.tar.xz",
+        "downloadname2": "Nordic-darker.tar.xz"
+    }]
+}"#;
+
+/// What Pling sends back for `content/data?search=Nordic&format=json`.
+const SEARCH_RESPONSE: &str = r#"{
+    "status": "ok",
+    "statuscode": 100,
+    "message": "",
+    "totalitems": 2,
+    "data": [{
+        "id": 1267250,
+        "name": "Nordic Cursors",
+        "personid": "someone-else",
+        "downloadname1": "Nordic-cursors.tar.xz"
+    }, {
+        "id": 1267246,
+        "name": "Nordic",
+        "personid": "eliverlara",
+        "downloadname1": "Nordic.tar.xz",
+        "downloadname2": "Nordic-darker.tar.xz"
+    }]
+}"#;
+
+fn client_for(server: &str) -> OcsClient {
+    OcsClient::new(Url::parse(&format!("{server}/ocs/v1")).unwrap())
+}
+
+#[test]
+fn content_from_pling() {
+    let server = serve(200, PLING_RESPONSE.into(), false);
+    let item = client_for(&server).content("1267246").unwrap();
+
+    assert_eq!(item.id, "1267246");
+    assert_eq!(item.name, "Nordic");
+    assert_eq!(item.author.as_deref(), Some("eliverlara"));
+    assert_eq!(item.version.as_deref(), Some("2.2.0"));
+    assert_eq!(item.license.as_deref(), Some("GPLv3"));
+    assert_eq!(
+        item.previews,
+        vec![
+            Url::parse("https://images.pling.com/img/nordic-1.png").unwrap(),
+            Url::parse("https://images.pling.com/img/nordic-2.png").unwrap(),
+        ]
+    );
+    assert_eq!(item.small_previews.len(), 1);
+    assert_eq!(item.files, vec!["Nordic.tar.xz", "Nordic-darker.tar.xz"]);
+}
+
+#[test]
+fn content_from_the_spec() {
+    let spec_response = r#"{"ocs": {
+        "meta": {"status": "ok", "statuscode": "100", "message": ""},
+        "data": {"content": {"id": "42", "name": "Spec", "previewpic1": ""}}
+    }}"#;
+    let server = serve(200, spec_response.into(), false);
+    let item = client_for(&server).content("42").unwrap();
+
+    assert_eq!(item.id, "42");
+    assert_eq!(item.name, "Spec");
+    assert!(item.author.is_none());
+    assert!(item.previews.is_empty());
+}
+
+#[test]
+fn content_errors() {
+    let failed = r#"{"status": "failed", "statuscode": 101, "message": "content not found"}"#;
+    let server = serve(200, failed.into(), false);
+    assert!(matches!(
+        client_for(&server).content("1"),
+        Err(OcsApiError::Api { code: 101, message }) if message == "content not found"
+    ));
+
+    let server = serve(500, Vec::new(), false);
+    assert!(matches!(
+        client_for(&server).content("1"),
+        Err(OcsApiError::HttpStatus { status: 500, .. })
+    ));
+
+    let server = serve(200, b"<ocs>xml!</ocs>".to_vec(), false);
+    assert!(matches!(
+        client_for(&server).content("1"),
+        Err(OcsApiError::BadResponse(_))
+    ));
+}
+
+#[test]
+fn content_ids_come_from_store_pages() {
+    let page = |url: &str| content_id_from_page(&Url::parse(url).unwrap());
+
+    assert_eq!(
+        page("https://www.pling.com/p/1267246/").as_deref(),
+        Some("1267246")
+    );
+    assert_eq!(
+        page("https://store.kde.org/p/1267246").as_deref(),
+        Some("1267246")
+    );
+    assert_eq!(page("https://www.pling.com/p/nordic/"), None);
+
+    // download URLs from real links don't carry it
+    let link = check_url(
+        "ocs://install?url=https%3A%2F%2Ffiles.pling.com%2Fapi%2Ffiles%2Fdownload%2Fj%2FeyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9.eyJpZCI6MTY3MjY4NDg5NX0.c2lnbmF0dXJl%2FNordic.tar.xz&type=gtk3_themes&filename=Nordic.tar.xz".into(),
+    )
+    .unwrap();
+    assert_eq!(content_id_from_page(&link.download_url), None);
+    let link = check_url(
+        "ocs://install?url=https%3A%2F%2Fdl.opendesktop.org%2Fapi%2Ffiles%2Fdownload%2Fid%2F1460735460%2Fs%2F6a1b%2Ft%2F1682000000%2FNordic.tar.xz&type=gtk3_themes".into(),
+    )
+    .unwrap();
+    assert_eq!(content_id_from_page(&link.download_url), None);
+}
+
+#[test]
+fn content_for_a_store_page() {
+    let server = serve(200, PLING_RESPONSE.into(), false);
+    let client = client_for(&server);
+
+    let page = Url::parse("https://www.pling.com/p/1267246/").unwrap();
+    assert_eq!(client.content_for_page(&page).unwrap().previews.len(), 2);
+
+    let page = Url::parse("https://www.pling.com/browse/").unwrap();
+    assert!(matches!(
+        client.content_for_page(&page),
+        Err(OcsApiError::NoContentId)
+    ));
+}
+
+#[test]
+fn content_ids_cant_change_the_request() {
+    let server = serve(200, PLING_RESPONSE.into(), false);
+    let client = client_for(&server);
+
+    for id in [
+        "../../content/data",
+        "1?search=x",
+        "1#x",
+        "1/2",
+        "%2E%2E",
+        "",
+    ] {
+        assert!(
+            matches!(client.content(id), Err(OcsApiError::BadContentId(bad)) if bad == id),
+            "{id}"
+        );
+    }
+}
+
+#[test]
+fn content_for_a_link() {
+    let server = serve(200, SEARCH_RESPONSE.into(), false);
+    let client = client_for(&server);
+
+    let link = check_url(link_to(&server, "/files/Nordic-darker.tar.xz", None)).unwrap();
+    let item = client.content_for_link(&link).unwrap();
+    assert_eq!(item.id, "1267246");
+    assert_eq!(item.author.as_deref(), Some("eliverlara"));
+
+    // searching finds things that only look like it
+    let link = check_url(link_to(&server, "/files/Nordic-blue.tar.xz", None)).unwrap();
+    assert!(matches!(
+        client.content_for_link(&link),
+        Err(OcsApiError::NoMatchingContent(filename)) if filename == "Nordic-blue.tar.xz"
+    ));
+}
+
+#[test]
+fn store_downloads() {
+    let store = |url: &str| is_store_download(&Url::parse(url).unwrap());
+
+    assert!(store(
+        "https://files.pling.com/api/files/download/j/x/Nordic.tar.xz"
+    ));
+    assert!(store(
+        "https://dl.opendesktop.org/api/files/download/id/1/Nordic.tar.xz"
+    ));
+    assert!(store("https://pling.com/Nordic.tar.xz"));
+    assert!(!store("https://notpling.com/Nordic.tar.xz"));
+    assert!(!store(
+        "https://github.com/EliverLara/Nordic/archive/master.zip"
+    ));
+}
@@ -16,6 +16,46 @@ fn url_parse_good_link() {
     assert_eq!(good_link, parsed_good_link.unwrap().to_string());
 }
 
+#[test]
+fn url_parse_xdg_links() {
+    // Some stores emit xdg:// links. They should work just like ocs:// ones.
+    for scheme in ["xdg", "xdgs", "ocss"] {
+        let link = new_link(Scheme, scheme);
+        let parsed = check_url(link.clone());
+
+        assert!(parsed.is_ok());
+        assert_eq!(link, parsed.unwrap().to_string());
+    }
+
+    assert_eq!(
+        check_url(new_link(Scheme, "xdg")).unwrap().scheme,
+        crate::types::Scheme::Xdg
+    );
+}
+
+#[test]
+fn url_parse_install_types() {
+    use crate::types::install_type::{self, AppSpecific, Styling};
+
+    // The alias resolves to a real install type...
+    let parsed = check_url(new_link(InstallType, "gtk3_themes")).unwrap();
+    assert_eq!(
+        parsed.install_type,
+        install_type::InstallType::Styling(Styling::GTK3Themes)
+    );
+
+    // ...but we keep the alias around so the link can be rebuilt as given.
+    assert_eq!(parsed.install_type_alias, "gtk3_themes");
+    assert_eq!(parsed.to_string(), new_link(InstallType, "gtk3_themes"));
+
+    assert_eq!(
+        check_url(new_link(InstallType, "nautilus_scripts"))
+            .unwrap()
+            .install_type,
+        install_type::InstallType::AppSpecific(AppSpecific::NautiliusScripts)
+    );
+}
+
 #[test]
 fn parse_bad_link() {
     // Evil, scary link.
@@ -47,13 +87,10 @@ fn parse_weird_link() {
             url::ParseError::RelativeUrlWithoutBase
         ))
     );
-    // ...
-    // TODO: uncomment when check_url actually checks types
-    // ...
-    /*assert_eq!(
+    assert_eq!(
         check_url(new_link(InstallType, weird)),
         Err(OcsParsingError::UnknownInstallType(weird.into()))
-    ); */
+    );
     assert!(check_url(new_link(Filename, weird)).is_ok());
 }
 
@@ -63,7 +100,10 @@ fn parse_blank_link() {
     assert!(check_url(new_link(Scheme, "")).is_err());
     assert!(check_url(new_link(Command, "")).is_err());
     assert!(check_url(new_link(DownloadUrl, "")).is_err());
-    // assert!(check_url(new_link(InstallType, "")).is_err()); TODO
+    assert_eq!(
+        check_url(new_link(InstallType, "")),
+        Err(OcsParsingError::NoInstallType)
+    );
     assert!(check_url(new_link(Filename, "")).is_ok());
 }
 
@@ -76,7 +116,7 @@ fn parse_loooong_link() {
     assert!(check_url(new_link(Scheme, long.as_str())).is_err());
     assert!(check_url(new_link(Command, long.as_str())).is_err());
     assert!(check_url(new_link(DownloadUrl, format!("https://{long})").as_str())).is_ok());
-    // assert!(check_url(new_link(InstallType, long.as_str())).is_err()); TODO
+    assert!(check_url(new_link(InstallType, long.as_str())).is_err());
     assert!(check_url(new_link(Filename, long.as_str())).is_ok());
 }
 
@@ -90,6 +130,6 @@ fn parse_crazy_link() {
     assert!(check_url(new_link(Scheme, crazy)).is_err());
     assert!(check_url(new_link(Command, crazy)).is_err());
     assert!(check_url(new_link(DownloadUrl, crazy)).is_err());
-    // assert!(check_url(new_link(InstallType, crazy)).is_err()); TODO
+    assert!(check_url(new_link(InstallType, crazy)).is_err());
     assert!(check_url(new_link(Filename, crazy)).is_ok());
 }
@@ -0,0 +1,164 @@
+#![allow(unused)]
+use crate::paths::{host_vars, PathError, PathResolver};
+use crate::types::install_type::*;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Makes a resolver that only knows about the given variables.
+fn resolver_with(vars: &[(&str, &Path)]) -> PathResolver {
+    PathResolver::with_vars(
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string_lossy().into_owned()))
+            .collect(),
+    )
+}
+
+#[test]
+fn xdg_defaults() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_with(&[("HOME", home.path())]);
+
+    assert_eq!(
+        resolver.resolve("$HOME/.themes"),
+        Ok(home.path().join(".themes"))
+    );
+    assert_eq!(
+        resolver.resolve("$XDG_DATA_HOME/icons"),
+        Ok(home.path().join(".local/share/icons"))
+    );
+    assert_eq!(
+        resolver.resolve("$APP_DATA/books"),
+        Ok(home.path().join(".local/share/amizade/books"))
+    );
+    assert_eq!(
+        resolver.resolve("$KDEHOME/share/apps/yakuake/skins"),
+        Ok(home.path().join(".kde/share/apps/yakuake/skins"))
+    );
+    assert_eq!(resolver.xdg_config_home(), Ok(home.path().join(".config")));
+}
+
+#[test]
+fn xdg_overrides() {
+    let home = tempfile::tempdir().unwrap();
+    let data = tempfile::tempdir().unwrap();
+    let resolver = resolver_with(&[("HOME", home.path()), ("XDG_DATA_HOME", data.path())]);
+
+    assert_eq!(
+        resolver.install_path(&Styling::Icons),
+        Ok(data.path().join("icons"))
+    );
+    assert_eq!(
+        resolver.install_path(&PersonalMedia::Comics),
+        Ok(data.path().join("amizade/comics"))
+    );
+
+    // relative paths aren't allowed by the spec, so they're ignored
+    let resolver = resolver_with(&[("HOME", home.path()), ("XDG_DATA_HOME", Path::new("rel"))]);
+    assert_eq!(
+        resolver.resolve("$XDG_DATA_HOME"),
+        Ok(home.path().join(".local/share"))
+    );
+}
+
+#[test]
+fn xdg_user_dirs() {
+    let home = tempfile::tempdir().unwrap();
+    let config = tempfile::tempdir().unwrap();
+    let resolver = resolver_with(&[("HOME", home.path()), ("XDG_CONFIG_HOME", config.path())]);
+
+    // without a user-dirs.dirs, we get the usual names
+    assert_eq!(
+        resolver.install_path(&PersonalMedia::Music),
+        Ok(home.path().join("Music"))
+    );
+
+    std::fs::write(
+        config.path().join("user-dirs.dirs"),
+        "# This file is written by xdg-user-dirs-update\n\
+         XDG_MUSIC_DIR=\"$HOME/Música\"\n\
+         XDG_PICTURES_DIR=\"/mnt/shared/Imagens\"\n\
+         XDG_VIDEOS_DIR=\"~/nope\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        resolver.install_path(&PersonalMedia::Music),
+        Ok(home.path().join("Música"))
+    );
+    assert_eq!(
+        resolver.install_path(&PersonalMedia::Pictures),
+        Ok(PathBuf::from("/mnt/shared/Imagens"))
+    );
+
+    // invalid or missing lines get the fallback
+    assert_eq!(
+        resolver.install_path(&PersonalMedia::Videos),
+        Ok(home.path().join("Videos"))
+    );
+    assert_eq!(
+        resolver.install_path(&PersonalMedia::Downloads),
+        Ok(home.path().join("Downloads"))
+    );
+}
+
+#[test]
+fn every_install_type_resolves() {
+    let home = tempfile::tempdir().unwrap();
+    let resolver = resolver_with(&[("HOME", home.path())]);
+
+    for install_type in InstallType::all() {
+        let path = resolver.install_path(&install_type).unwrap();
+        assert!(path.is_absolute(), "{install_type:?}: {path:?}");
+    }
+}
+
+#[test]
+fn bad_paths() {
+    assert_eq!(
+        PathResolver::with_vars(HashMap::new()).resolve("$HOME/.fonts"),
+        Err(PathError::NoHome)
+    );
+    assert_eq!(
+        resolver_with(&[("HOME", Path::new("/home/me"))]).resolve("$FARTS/icons"),
+        Err(PathError::UnknownPlaceholder("FARTS".into()))
+    );
+}
+
+#[test]
+fn flatpaks_use_the_hosts_dirs() {
+    let vars = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    };
+    let sandbox = [
+        ("HOME", "/home/me"),
+        (
+            "XDG_DATA_HOME",
+            "/home/me/.var/app/club.barretts.Amizade/data",
+        ),
+        (
+            "XDG_CONFIG_HOME",
+            "/home/me/.var/app/club.barretts.Amizade/config",
+        ),
+    ];
+
+    // outside of a flatpak, nothing changes
+    assert_eq!(host_vars(vars(&sandbox)), vars(&sandbox));
+
+    let mut flatpak = sandbox.to_vec();
+    flatpak.push(("FLATPAK_ID", "club.barretts.Amizade"));
+    flatpak.push(("HOST_XDG_CONFIG_HOME", "/home/me/dotfiles"));
+    let resolver = PathResolver::with_vars(host_vars(vars(&flatpak)));
+
+    assert_eq!(
+        resolver.xdg_data_home(),
+        Ok(PathBuf::from("/home/me/.local/share"))
+    );
+    assert_eq!(
+        resolver.xdg_config_home(),
+        Ok(PathBuf::from("/home/me/dotfiles"))
+    );
+}
@@ -0,0 +1,296 @@
+#![allow(unused)]
+use crate::handler::DownloadError;
+use crate::installer::{ConflictPolicy, InstallError, Installer};
+use crate::parser::check_url;
+use crate::plan::{FileAction, PlannedFile, PostInstallAction};
+use crate::progress::{Cancellation, InstallEvent, InstallStage};
+use crate::tests::test_helpers::{installer_for, link_to, serve, tar_gz};
+
+use std::fs;
+
+fn papirus() -> Vec<u8> {
+    tar_gz(&[
+        ("Papirus/index.theme", b"[Icon Theme]"),
+        ("Papirus/16x16/a.svg", b"<svg/>"),
+    ])
+}
+
+#[test]
+fn plans_only_write_to_staging() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let plan = installer.plan(&link, |_| ()).unwrap();
+
+    let icons = home.path().join(".local/share/icons");
+    assert_eq!(plan.destination, icons);
+    assert_eq!(plan.items, vec![icons.join("Papirus")]);
+    assert_eq!(
+        plan.files,
+        vec![
+            PlannedFile {
+                path: icons.join("Papirus/index.theme"),
+                size: 12,
+                action: FileAction::Create,
+            },
+            PlannedFile {
+                path: icons.join("Papirus/16x16/a.svg"),
+                size: 6,
+                action: FileAction::Create,
+            },
+        ]
+    );
+    assert!(plan.conflicts.is_empty());
+    assert_eq!(plan.install_size, 18);
+    assert_eq!(plan.download_size, papirus().len() as u64);
+    assert!(plan.post_install.is_empty());
+
+    let staging = home.path().join(".local/share/amizade/staging");
+    assert!(plan.staged.starts_with(&staging));
+    assert_eq!(fs::read(&plan.staged).unwrap(), papirus());
+    assert!(!icons.exists());
+
+    plan.discard(&installer.resolver);
+    assert_eq!(fs::read_dir(staging).unwrap().count(), 0);
+}
+
+#[test]
+fn plans_install_what_they_downloaded() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let plan = installer.plan(&link, |_| ()).unwrap();
+
+    let mut events = Vec::new();
+    let report = installer
+        .install_plan(&plan, &Cancellation::new(), |event| events.push(event))
+        .unwrap();
+
+    // nothing was downloaded again
+    assert_eq!(events[0], InstallEvent::Stage(InstallStage::Extracting));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, InstallEvent::Download(_))));
+    assert_eq!(report.items, plan.items);
+    assert_eq!(
+        fs::read(home.path().join(".local/share/icons/Papirus/index.theme")).unwrap(),
+        b"[Icon Theme]"
+    );
+
+    let staging = home.path().join(".local/share/amizade/staging");
+    assert_eq!(fs::read_dir(staging).unwrap().count(), 0);
+}
+
+#[test]
+fn plans_refuse_changed_downloads() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let plan = installer.plan(&link, |_| ()).unwrap();
+    fs::write(&plan.staged, tar_gz(&[("Evil/index.theme", b"boo")])).unwrap();
+
+    assert!(matches!(
+        installer.install_plan(&plan, &Cancellation::new(), |_| ()),
+        Err(InstallError::ChangedSincePlanned(path)) if path == plan.staged
+    ));
+    assert!(!home.path().join(".local/share/icons").exists());
+    assert!(!plan.staged.exists());
+}
+
+#[test]
+fn plans_only_touch_their_own_staging() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let mut plan = installer.plan(&link, |_| ()).unwrap();
+    let real = plan.staged.clone();
+
+    // like a plan that was read back from JSON after someone edited it
+    let documents = home.path().join("Documents");
+    fs::create_dir_all(&documents).unwrap();
+    fs::copy(&real, documents.join("papirus.tar.gz")).unwrap();
+    let staging = home.path().join(".local/share/amizade/staging");
+    for staged in [
+        documents.join("papirus.tar.gz"),
+        staging.join("papirus.tar.gz"),
+        staging.join("../papirus.tar.gz"),
+    ] {
+        plan.staged = staged;
+        plan.discard(&installer.resolver);
+        assert!(matches!(
+            installer.install_plan(&plan, &Cancellation::new(), |_| ()),
+            Err(InstallError::NotStaged(_))
+        ));
+    }
+    assert!(documents.join("papirus.tar.gz").exists());
+    assert!(real.exists());
+    assert!(!home.path().join(".local/share/icons").exists());
+
+    plan.staged = real;
+    plan.discard(&installer.resolver);
+    assert_eq!(fs::read_dir(staging).unwrap().count(), 0);
+}
+
+#[test]
+fn plans_check_their_destination_again() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let mut installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let plan = installer.plan(&link, |_| ()).unwrap();
+
+    installer
+        .config
+        .set_type_override("icons", "$HOME/elsewhere", &installer.resolver.clone())
+        .unwrap();
+    assert!(matches!(
+        installer.install_plan(&plan, &Cancellation::new(), |_| ()),
+        Err(InstallError::DestinationChanged(path)) if path == home.path().join("elsewhere")
+    ));
+    assert!(!home.path().join(".local/share/icons").exists());
+}
+
+#[test]
+fn plans_refuse_downloads_too_big_to_unpack() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let mut installer = installer_for(home.path());
+    installer.extract_limits.max_total_size = 10;
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    assert!(matches!(
+        installer.plan(&link, |_| ()),
+        Err(InstallError::DownloadError(DownloadError::TooBig(10)))
+    ));
+
+    let staging = home.path().join(".local/share/amizade/staging");
+    assert_eq!(fs::read_dir(staging).unwrap().count(), 0);
+}
+
+#[test]
+fn plans_match_installs() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let installer = installer_for(home.path());
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let plan = installer.plan(&link, |_| ()).unwrap();
+    let report = installer.install(&link, |_| ()).unwrap();
+
+    let mut planned: Vec<_> = plan.files.into_iter().map(|file| file.path).collect();
+    planned.sort();
+    assert_eq!(plan.items, report.items);
+    assert_eq!(planned, report.files);
+}
+
+#[test]
+fn plans_show_conflicts() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let mut installer = installer_for(home.path());
+
+    let papirus_dir = home.path().join(".local/share/icons/Papirus");
+    fs::create_dir_all(&papirus_dir).unwrap();
+    fs::write(papirus_dir.join("index.theme"), "old").unwrap();
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+
+    installer.conflict_policy = ConflictPolicy::Overwrite;
+    let plan = installer.plan(&link, |_| ()).unwrap();
+    assert_eq!(plan.conflicts[0].existing, papirus_dir);
+    assert_eq!(plan.files[0].action, FileAction::Overwrite);
+    assert_eq!(plan.files[1].action, FileAction::Create);
+
+    installer.conflict_policy = ConflictPolicy::KeepBoth;
+    let plan = installer.plan(&link, |_| ()).unwrap();
+    assert_eq!(plan.items, vec![papirus_dir.with_file_name("Papirus-2")]);
+    assert_eq!(plan.files[0].action, FileAction::Create);
+
+    installer.conflict_policy = ConflictPolicy::Skip;
+    let plan = installer.plan(&link, |_| ()).unwrap();
+    assert!(plan.items.is_empty());
+    assert!(plan.files.is_empty());
+
+    // and nothing was touched
+    assert_eq!(fs::read(papirus_dir.join("index.theme")).unwrap(), b"old");
+}
+
+#[test]
+fn plans_show_legacy_duplicates() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let mut installer = installer_for(home.path());
+
+    let legacy = home.path().join(".icons/Papirus");
+    fs::create_dir_all(&legacy).unwrap();
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let mut plan = installer.plan(&link, |_| ()).unwrap();
+    assert_eq!(plan.conflicts[0].existing, legacy);
+    assert_eq!(
+        plan.conflicts[0].moved_to,
+        Some(home.path().join(".icons/Papirus.bak"))
+    );
+    assert_eq!(
+        plan.items,
+        vec![home.path().join(".local/share/icons/Papirus")]
+    );
+
+    plan.set_conflict_policy(ConflictPolicy::Skip);
+    assert!(plan.items.is_empty());
+    assert_eq!(plan.conflicts[0].existing, legacy);
+    plan.discard(&installer.resolver);
+}
+
+#[test]
+fn plans_change_policy_without_downloading() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, papirus(), false);
+    let mut installer = installer_for(home.path());
+
+    let papirus_dir = home.path().join(".local/share/icons/Papirus");
+    fs::create_dir_all(&papirus_dir).unwrap();
+
+    let link = check_url(link_to(&server, "/dl/papirus.tar.gz", None)).unwrap();
+    let mut plan = installer.plan(&link, |_| ()).unwrap();
+
+    for policy in ConflictPolicy::ALL {
+        plan.set_conflict_policy(policy);
+
+        installer.conflict_policy = policy;
+        let fresh = installer.plan(&link, |_| ()).unwrap();
+        fresh.discard(&installer.resolver);
+
+        assert_eq!(plan.conflict_policy, policy);
+        assert_eq!(plan.items, fresh.items);
+        assert_eq!(plan.files, fresh.files);
+        assert_eq!(plan.conflicts, fresh.conflicts);
+        assert_eq!(plan.install_size, fresh.install_size);
+    }
+
+    // the same download is still there to install
+    assert!(plan.staged.exists());
+    plan.discard(&installer.resolver);
+}
+
+#[test]
+fn plans_list_post_install_actions() {
+    let home = tempfile::tempdir().unwrap();
+    let server = serve(200, b"font".to_vec(), false);
+    let installer = installer_for(home.path());
+
+    let link = link_to(&server, "/dl/font.ttf", None).replace("icons", "fonts");
+    let plan = installer.plan(&check_url(link).unwrap(), |_| ()).unwrap();
+
+    assert_eq!(plan.post_install, vec![PostInstallAction::UpdateFontCache]);
+    assert_eq!(plan.files[0].size, 4);
+}
@@ -1,4 +1,10 @@
 #![allow(dead_code)]
+use crate::config::Config;
+use crate::installer::Installer;
+use crate::paths::PathResolver;
+
+use std::collections::HashMap;
+use std::path::Path;
 use urlencoding::encode;
 use LinkParts::*;
 
@@ -30,3 +36,80 @@ pub fn new_link(chosen: LinkParts, difference: &str) -> String {
 
     format!("{scheme}://{command}?url={download}&type={install_type}&filename={filename}")
 }
+
+/// Starts a tiny HTTP server on localhost that answers every request with the
+/// given status and body. Returns its address, like `http://127.0.0.1:1234`.
+///
+/// If `stall` is set, it'll accept connections but never answer them.
+pub fn serve(status: u16, body: Vec<u8>, stall: bool) -> String {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+
+            if stall {
+                std::thread::sleep(std::time::Duration::from_secs(5));
+                continue;
+            }
+
+            // read (and ignore) the request's headers
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok() && line != "\r\n" && !line.is_empty() {
+                line.clear();
+            }
+
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status} Whatever\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(&body);
+        }
+    });
+
+    address
+}
+
+/// Makes a link that downloads `path` from a server made with [`serve`].
+pub fn link_to(server: &str, path: &str, filename: Option<&str>) -> String {
+    let mut link = format!(
+        "ocs://install?url={}&type=icons",
+        encode(&format!("{server}{path}"))
+    );
+    if let Some(filename) = filename {
+        link.push_str(&format!("&filename={filename}"));
+    }
+
+    link
+}
+
+/// An installer that lives entirely inside of `home`.
+pub fn installer_for(home: &Path) -> Installer {
+    let resolver = PathResolver::with_vars(HashMap::from([(
+        "HOME".into(),
+        home.to_string_lossy().into_owned(),
+    )]));
+
+    Installer::new(resolver, Config::default())
+}
+
+/// A gzipped tarball holding `files`.
+pub fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, path, *data).unwrap();
+    }
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+    std::io::Write::write_all(&mut encoder, &builder.into_inner().unwrap()).unwrap();
+    encoder.finish().unwrap()
+}
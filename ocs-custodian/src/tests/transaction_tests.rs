@@ -0,0 +1,72 @@
+#![allow(unused)]
+use crate::transaction::Transaction;
+
+use std::fs;
+
+#[test]
+fn commit_keeps_new_items() {
+    let dir = tempfile::tempdir().unwrap();
+    let destination = dir.path().join("icons");
+    fs::create_dir_all(&destination).unwrap();
+    fs::write(destination.join("theme"), "old").unwrap();
+    fs::write(dir.path().join("theme"), "new").unwrap();
+
+    let mut transaction = Transaction::begin(&destination).unwrap();
+    let staged = transaction.stage(&dir.path().join("theme")).unwrap();
+    transaction
+        .place(&staged, &destination.join("theme"))
+        .unwrap();
+    transaction.commit().unwrap();
+
+    assert_eq!(fs::read(destination.join("theme")).unwrap(), b"new");
+    // no backups or staging directories are left over
+    assert_eq!(fs::read_dir(&destination).unwrap().count(), 1);
+}
+
+#[test]
+fn dropping_rolls_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let destination = dir.path().join("icons");
+    fs::create_dir_all(destination.join("Papirus")).unwrap();
+    fs::write(destination.join("Papirus/index.theme"), "old").unwrap();
+
+    fs::create_dir_all(dir.path().join("new/Papirus")).unwrap();
+    fs::write(dir.path().join("new/Papirus/index.theme"), "new").unwrap();
+    fs::write(dir.path().join("new/Nordic"), "new").unwrap();
+
+    {
+        let mut transaction = Transaction::begin(&destination).unwrap();
+        for name in ["Papirus", "Nordic"] {
+            let staged = transaction
+                .stage(&dir.path().join("new").join(name))
+                .unwrap();
+            transaction.place(&staged, &destination.join(name)).unwrap();
+        }
+        assert!(destination.join("Nordic").exists());
+
+        // something went wrong, so the transaction is never committed
+    }
+
+    assert_eq!(
+        fs::read(destination.join("Papirus/index.theme")).unwrap(),
+        b"old"
+    );
+    assert!(!destination.join("Nordic").exists());
+    assert_eq!(fs::read_dir(&destination).unwrap().count(), 1);
+}
+
+#[test]
+fn rolling_back_removes_a_new_destination() {
+    let dir = tempfile::tempdir().unwrap();
+    let destination = dir.path().join("fonts");
+    fs::write(dir.path().join("font.ttf"), "font").unwrap();
+
+    let mut transaction = Transaction::begin(&destination).unwrap();
+    let staged = transaction.stage(&dir.path().join("font.ttf")).unwrap();
+    transaction
+        .place(&staged, &destination.join("font.ttf"))
+        .unwrap();
+    drop(transaction);
+
+    assert!(!destination.exists());
+}
@@ -0,0 +1,170 @@
+//! Puts items into place all at once, or not at all.
+//!
+//! Items are first copied into a hidden directory right next to where they're
+//! going, so that they're on the same filesystem. From there, each one is put
+//! in place with a rename, and anything it replaces is renamed out of the way
+//! instead of deleted. If something goes wrong, the renames are undone.
+use crate::fs_util::{move_item, remove_item, unique_dir};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An install into one destination that hasn't been committed yet.
+///
+/// Dropping it without calling [`Transaction::commit`] rolls it back.
+#[derive(Debug)]
+pub(crate) struct Transaction {
+    destination: PathBuf,
+    /// Whether we made `destination`, so it should go away on rollback.
+    created_destination: bool,
+    /// The hidden directory inside of `destination` we work in.
+    staging: PathBuf,
+    steps: Vec<Step>,
+    done: bool,
+}
+
+/// Something that was done, and would need undoing.
+#[derive(Debug)]
+enum Step {
+    /// An item was put in place, replacing `backup` if there was one.
+    Placed {
+        target: PathBuf,
+        backup: Option<PathBuf>,
+    },
+    /// Something that was already there was renamed.
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl Transaction {
+    /// Starts installing into `destination`, making it if needed.
+    pub(crate) fn begin(destination: &Path) -> io::Result<Self> {
+        let created_destination = !destination.exists();
+        fs::create_dir_all(destination)?;
+
+        let staging = match unique_dir(destination, ".amizade-staging") {
+            Ok(staging) => staging,
+            Err(e) => {
+                if created_destination {
+                    let _ = fs::remove_dir(destination);
+                }
+                return Err(e);
+            }
+        };
+
+        Ok(Self {
+            destination: destination.to_path_buf(),
+            created_destination,
+            staging,
+            steps: Vec::new(),
+            done: false,
+        })
+    }
+
+    /// Moves `item` next to the destination, so that placing it is just a
+    /// rename. Returns where it ended up.
+    pub(crate) fn stage(&self, item: &Path) -> io::Result<PathBuf> {
+        let new = self.staging.join("new");
+        fs::create_dir_all(&new)?;
+
+        let staged = new.join(item.file_name().unwrap_or(item.as_os_str()));
+        move_item(item, &staged)?;
+
+        Ok(staged)
+    }
+
+    /// Renames a staged item to `target`. Whatever was at `target` is kept
+    /// aside until the transaction is committed.
+    pub(crate) fn place(&mut self, staged: &Path, target: &Path) -> io::Result<()> {
+        let backup = match target.symlink_metadata() {
+            Ok(_) => {
+                let old = self.staging.join("old");
+                fs::create_dir_all(&old)?;
+
+                let backup = old.join(self.steps.len().to_string());
+                fs::rename(target, &backup)?;
+                Some(backup)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        if let Err(e) = fs::rename(staged, target) {
+            if let Some(backup) = backup {
+                let _ = fs::rename(backup, target);
+            }
+            return Err(e);
+        }
+
+        self.steps.push(Step::Placed {
+            target: target.to_path_buf(),
+            backup,
+        });
+
+        Ok(())
+    }
+
+    /// Renames something that's in the way, so that it's kept even after the
+    /// transaction is committed.
+    pub(crate) fn rename(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)?;
+        self.steps.push(Step::Renamed {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+
+        Ok(())
+    }
+
+    /// Moves something out of the way for good. It's only deleted once the
+    /// transaction is committed.
+    pub(crate) fn remove(&mut self, item: &Path) -> io::Result<()> {
+        let old = self.staging.join("old");
+        fs::create_dir_all(&old)?;
+
+        let removed = old.join(format!("removed-{}", self.steps.len()));
+        self.rename(item, &removed)
+    }
+
+    /// Keeps everything that was placed, and throws away what it replaced.
+    pub(crate) fn commit(mut self) -> io::Result<()> {
+        self.done = true;
+        remove_item(&self.staging)
+    }
+
+    /// Undoes everything that was placed or renamed, and puts back what it
+    /// replaced.
+    /// Keeps going after errors, so that as much as possible gets restored.
+    fn undo(&mut self) {
+        self.done = true;
+
+        for step in self.steps.drain(..).rev() {
+            match step {
+                Step::Placed { target, backup } => {
+                    if remove_item(&target).is_ok() {
+                        if let Some(backup) = backup {
+                            let _ = fs::rename(backup, &target);
+                        }
+                    }
+                }
+                Step::Renamed { from, to } => {
+                    let _ = fs::rename(to, from);
+                }
+            }
+        }
+
+        let _ = remove_item(&self.staging);
+        if self.created_destination {
+            // only if it's still empty
+            let _ = fs::remove_dir(&self.destination);
+        }
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if !self.done {
+            self.undo();
+        }
+    }
+}
@@ -4,7 +4,7 @@ use thiserror::Error;
 use url::Url;
 
 pub mod install_type;
-pub use install_type::InstallTypeError;
+pub use install_type::{InstallType, InstallTypeError};
 
 /// Represents one of many parsing errors that can occur when parsing OCS links.
 #[derive(Error, Debug, PartialEq, Eq)]
@@ -15,7 +15,7 @@ pub enum OcsParsingError {
     UrlParsingError(#[from] url::ParseError),
     #[error("No OCS Scheme was provided. Please try a link like: `ocs://...`")]
     NoOcsScheme,
-    #[error("An unexpected OCS Scheme was provided: `{0}`. Instead, please use `ocs://...`")]
+    #[error("An unexpected OCS Scheme was provided: `{0}`. Instead, please use `ocs://...` or `xdg://...`")]
     UnexpectedOcsScheme(String),
     #[error("No OCS Command was provided. Try a link like: `ocs://install...`")]
     NoOcsCommand,
@@ -29,21 +29,64 @@ pub enum OcsParsingError {
     NoInstallType,
     #[error(transparent)]
     InstallTypeError(#[from] InstallTypeError),
+    #[error("The link's fields don't match its OCS URL: `{0}`")]
+    MismatchedFields(String),
 }
 
 /// A representation of the most important elements of an OCS link.
 /// The original URL is included as `ocs_url`, and the download URL can
 /// be reached using the `download_url`.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedOcsUrl"))]
 pub struct ParsedOcsUrl {
     pub ocs_url: Url, // the "full" url. e.g. ocs://etc
     pub scheme: Scheme,
     pub command: Command,
     pub download_url: Url,
-    pub install_type: String, // include aliases
+    pub install_type: InstallType,
+    pub install_type_alias: String, // the alias as given, e.g. `gtk3_themes`
     pub filename: Option<String>,
 }
 
+/// A [`ParsedOcsUrl`] that was read from somewhere else, like JSON. It's only
+/// trusted once its fields match what `ocs_url` parses to, since the installer
+/// goes by the fields.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedOcsUrl {
+    ocs_url: Url,
+    scheme: Scheme,
+    command: Command,
+    download_url: Url,
+    install_type: InstallType,
+    install_type_alias: String,
+    filename: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedOcsUrl> for ParsedOcsUrl {
+    type Error = OcsParsingError;
+
+    fn try_from(unchecked: UncheckedOcsUrl) -> Result<Self, Self::Error> {
+        let parsed = crate::parser::check_url(unchecked.ocs_url.to_string())?;
+        let claimed = Self {
+            ocs_url: unchecked.ocs_url,
+            scheme: unchecked.scheme,
+            command: unchecked.command,
+            download_url: unchecked.download_url,
+            install_type: unchecked.install_type,
+            install_type_alias: unchecked.install_type_alias,
+            filename: unchecked.filename,
+        };
+
+        match parsed == claimed {
+            true => Ok(parsed),
+            false => Err(OcsParsingError::MismatchedFields(claimed.ocs_url.into())),
+        }
+    }
+}
+
 impl Display for ParsedOcsUrl {
     /// Allows for getting a ParsedOcsUrl back as a String.
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -53,19 +96,12 @@ impl Display for ParsedOcsUrl {
             self.scheme,
             self.command,
             urlencoding::encode(self.download_url.as_str()),
-            self.install_type,
+            self.install_type_alias,
         )?;
 
         // If we have a filename, add it to the link
-        if self.filename.is_some() {
-            write!(
-                f,
-                "&filename={}",
-                self.filename
-                    .as_ref()
-                    .expect("filename should be some :)")
-                    .as_str()
-            )?;
+        if let Some(filename) = &self.filename {
+            write!(f, "&filename={}", filename)?;
         }
 
         // All good!
@@ -75,10 +111,17 @@ impl Display for ParsedOcsUrl {
 
 /// A representation of the OCS scheme. As of mid-2023, there's only ocs://
 /// available. ocss:// will represent a "secure" version of the protocol.
-#[derive(Debug, PartialEq, Eq)]
+///
+/// Some stores emit xdg:// (and xdgs://) links instead. These use the exact
+/// same grammar, so we take them too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Scheme {
     Ocs,
     Ocss,
+    Xdg,
+    Xdgs,
 }
 
 impl Display for Scheme {
@@ -87,6 +130,8 @@ impl Display for Scheme {
         let text = match self {
             Scheme::Ocs => "ocs",
             Scheme::Ocss => "ocss",
+            Scheme::Xdg => "xdg",
+            Scheme::Xdgs => "xdgs",
         };
 
         write!(f, "{}", text)
@@ -100,6 +145,8 @@ impl TryFrom<&str> for Scheme {
         match value.to_lowercase().as_str() {
             "ocs" => Ok(Self::Ocs),
             "ocss" => Ok(Self::Ocss),
+            "xdg" => Ok(Self::Xdg),
+            "xdgs" => Ok(Self::Xdgs),
             other => Err(OcsParsingError::UnexpectedOcsScheme(other.to_owned())),
         }
     }
@@ -107,7 +154,9 @@ impl TryFrom<&str> for Scheme {
 
 /// The intention of the URL - what the user asks you to do.
 /// Also known as a "host string" in general terms.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Command {
     Download, // pass to client.
     Install,  // we must install it. indicate success/failure
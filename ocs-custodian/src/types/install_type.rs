@@ -6,19 +6,225 @@
 //!
 //! If you think that you have a good way to do this without affecting runtime
 //! performance, please let me know in an issue. I'd love to take a look!
+//!
+//! Each category enum lists its own aliases, and [`InstallType`] ties all of
+//! them together. That's the one you'll usually want.
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Represents which kind of file should be processed.
 /// Helps when dealing with the many, MANY types of files Pling has to offer.
-pub struct InstallType {
-    install_type: dyn InstallStrategy,
+///
+/// This is the registry for every install type we know about, no matter which
+/// category it lives in.
+/// ```
+/// use ocs_custodian::types::install_type::{InstallStrategy, InstallType};
+///
+/// let gtk3 = InstallType::from_alias("gtk3_themes").unwrap();
+/// assert_eq!(gtk3.get_install_path(), "$XDG_DATA_HOME/themes");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "category", content = "kind"))]
+pub enum InstallType {
+    PersonalMedia(PersonalMedia),
+    Styling(Styling),
+    WMThemes(WMThemes),
+    QtGeneral(QtGeneral),
+    AppSpecific(AppSpecific),
+}
+
+impl InstallType {
+    /// Finds the install type that the given alias belongs to.
+    pub fn from_alias(alias: &str) -> Result<Self, InstallTypeError> {
+        Self::try_from(alias)
+    }
+
+    /// Every install type we know about, sorted by category.
+    pub fn all() -> Vec<Self> {
+        let mut all = Vec::new();
+        all.extend(PersonalMedia::ALL.iter().map(|t| Self::PersonalMedia(*t)));
+        all.extend(Styling::ALL.iter().map(|t| Self::Styling(*t)));
+        all.extend(WMThemes::ALL.iter().map(|t| Self::WMThemes(*t)));
+        all.extend(QtGeneral::ALL.iter().map(|t| Self::QtGeneral(*t)));
+        all.extend(AppSpecific::ALL.iter().map(|t| Self::AppSpecific(*t)));
+        all
+    }
+
+    /// Lists every known alias alongside its category and destination.
+    pub fn registry() -> Vec<AliasEntry> {
+        Self::all()
+            .into_iter()
+            .flat_map(|install_type| {
+                install_type.aliases().iter().map(move |alias| AliasEntry {
+                    alias,
+                    install_type,
+                    category: install_type.category(),
+                    destination: install_type.get_install_path(),
+                })
+            })
+            .collect()
+    }
+
+    /// The category that this install type is sorted into.
+    pub fn category(&self) -> InstallCategory {
+        match self {
+            Self::PersonalMedia(_) => InstallCategory::PersonalMedia,
+            Self::Styling(_) => InstallCategory::Styling,
+            Self::WMThemes(_) => InstallCategory::WMThemes,
+            Self::QtGeneral(_) => InstallCategory::QtGeneral,
+            Self::AppSpecific(_) => InstallCategory::AppSpecific,
+        }
+    }
+
+    /// Whether this install type goes somewhere outside of the user's home,
+    /// like `/usr/share/sddm/themes`. These need root to install.
+    pub fn is_system_wide(&self) -> bool {
+        !self.get_install_path().starts_with('$')
+    }
+
+    /// Every alias that links may use for this install type.
+    /// The first one is the "main" alias.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::PersonalMedia(kind) => kind.aliases(),
+            Self::Styling(kind) => kind.aliases(),
+            Self::WMThemes(kind) => kind.aliases(),
+            Self::QtGeneral(kind) => kind.aliases(),
+            Self::AppSpecific(kind) => kind.aliases(),
+        }
+    }
+}
+
+impl InstallStrategy for InstallType {
+    fn get_install_path(&self) -> String {
+        match self {
+            Self::PersonalMedia(kind) => kind.get_install_path(),
+            Self::Styling(kind) => kind.get_install_path(),
+            Self::WMThemes(kind) => kind.get_install_path(),
+            Self::QtGeneral(kind) => kind.get_install_path(),
+            Self::AppSpecific(kind) => kind.get_install_path(),
+        }
+    }
+
+    fn get_legacy_install_path(&self) -> Option<String> {
+        match self {
+            Self::PersonalMedia(kind) => kind.get_legacy_install_path(),
+            Self::Styling(kind) => kind.get_legacy_install_path(),
+            Self::WMThemes(kind) => kind.get_legacy_install_path(),
+            Self::QtGeneral(kind) => kind.get_legacy_install_path(),
+            Self::AppSpecific(kind) => kind.get_legacy_install_path(),
+        }
+    }
+
+    fn check_layout(&self, item: &Path) -> Result<(), InstallTypeError> {
+        match self {
+            Self::PersonalMedia(kind) => kind.check_layout(item),
+            Self::Styling(kind) => kind.check_layout(item),
+            Self::WMThemes(kind) => kind.check_layout(item),
+            Self::QtGeneral(kind) => kind.check_layout(item),
+            Self::AppSpecific(kind) => kind.check_layout(item),
+        }
+    }
+}
+
+impl TryFrom<&str> for InstallType {
+    type Error = InstallTypeError;
+
+    /// Tries the alias against each category, in order.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if let Ok(kind) = PersonalMedia::try_from(value) {
+            return Ok(Self::PersonalMedia(kind));
+        }
+        if let Ok(kind) = Styling::try_from(value) {
+            return Ok(Self::Styling(kind));
+        }
+        if let Ok(kind) = WMThemes::try_from(value) {
+            return Ok(Self::WMThemes(kind));
+        }
+        if let Ok(kind) = QtGeneral::try_from(value) {
+            return Ok(Self::QtGeneral(kind));
+        }
+        if let Ok(kind) = AppSpecific::try_from(value) {
+            return Ok(Self::AppSpecific(kind));
+        }
+
+        Err(InstallTypeError::NoInstallTypeAlias(value.into()))
+    }
+}
+
+/// The broad groups that install types are sorted into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InstallCategory {
+    PersonalMedia,
+    Styling,
+    WMThemes,
+    QtGeneral,
+    AppSpecific,
+}
+
+impl Display for InstallCategory {
+    /// Converts the enum to text.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let text = match self {
+            Self::PersonalMedia => "PersonalMedia",
+            Self::Styling => "Styling",
+            Self::WMThemes => "WMThemes",
+            Self::QtGeneral => "QtGeneral",
+            Self::AppSpecific => "AppSpecific",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+impl TryFrom<&str> for InstallCategory {
+    type Error = InstallTypeError;
+
+    /// Takes either the enum's name or its snake_case form, in any case.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().replace('_', "").as_str() {
+            "personalmedia" => Ok(Self::PersonalMedia),
+            "styling" => Ok(Self::Styling),
+            "wmthemes" => Ok(Self::WMThemes),
+            "qtgeneral" => Ok(Self::QtGeneral),
+            "appspecific" => Ok(Self::AppSpecific),
+            _ => Err(InstallTypeError::NoMatchingCategory(value.into())),
+        }
+    }
+}
+
+/// One row of the install type registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasEntry {
+    pub alias: &'static str,
+    pub install_type: InstallType,
+    pub category: InstallCategory,
+    pub destination: String,
 }
 
 /// Helps define how any specific InstallType should install itself to the system.
 pub trait InstallStrategy {
     /// Each InstallType requires a different installation location.
     /// When InstallStrategy is implemented, you can just get it for your type.
+    ///
+    /// The path may start with a placeholder, like `$XDG_DATA_HOME`. Use a
+    /// [`crate::paths::PathResolver`] to get a real path out of it.
     fn get_install_path(&self) -> String;
+
+    /// Where this InstallType used to be installed before it moved to its
+    /// current location, if it ever did. Things may still live there!
+    fn get_legacy_install_path(&self) -> Option<String> {
+        None
+    }
+
+    /// Checks that an item looks like it belongs to this InstallType before
+    /// it's installed. `item` is the top-level file or directory of the item.
+    fn check_layout(&self, _item: &Path) -> Result<(), InstallTypeError> {
+        Ok(())
+    }
 }
 
 /// Represents a failure to parse given install type data.
@@ -26,13 +232,30 @@ pub trait InstallStrategy {
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum InstallTypeError {
     #[error("No known install type matched the given prompt: {0}")]
-    NoMatchingInstallType(String), // TODO
+    NoMatchingInstallType(String),
     #[error("The given alias, {0}, didn't fit any existing type.")]
     NoInstallTypeAlias(String),
+    #[error("No install category is called `{0}`.")]
+    NoMatchingCategory(String),
+    #[error("`{item}` doesn't look right for its install type. Expected {expected} inside of it.")]
+    UnexpectedLayout { item: PathBuf, expected: String },
+}
+
+/// Finds the variant in `all` that lists `alias` as one of its aliases.
+fn find_alias<T: Copy>(
+    all: &[T],
+    aliases: fn(&T) -> &'static [&'static str],
+    alias: &str,
+) -> Result<T, InstallTypeError> {
+    all.iter()
+        .find(|kind| aliases(kind).contains(&alias))
+        .copied()
+        .ok_or_else(|| InstallTypeError::NoMatchingInstallType(alias.into()))
 }
 
 // personal media
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PersonalMedia {
     Bin,
     Books,
@@ -45,21 +268,51 @@ pub enum PersonalMedia {
     Wallpapers,
 }
 
+impl PersonalMedia {
+    pub const ALL: &'static [Self] = &[
+        Self::Bin,
+        Self::Books,
+        Self::Comics,
+        Self::Documents,
+        Self::Downloads,
+        Self::Music,
+        Self::Pictures,
+        Self::Videos,
+        Self::Wallpapers,
+    ];
+
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::Bin => &["bin"],
+            Self::Books => &["books"],
+            Self::Comics => &["comics"],
+            Self::Documents => &["documents"],
+            Self::Downloads => &["downloads"],
+            Self::Music => &["music"],
+            Self::Pictures => &["pictures"],
+            Self::Videos => &["videos"],
+            Self::Wallpapers => &["wallpapers"],
+        }
+    }
+}
+
 impl InstallStrategy for PersonalMedia {
     /// May use $APP_DATA to denote a place to save files.
-    /// $APP_DATA is defined as the where the application's configuration lives
-    /// For example, `$XDG_DATA_HOME/amizade`, etc...
-    // TODO: deal with $APP_DATA lmao
+    /// $APP_DATA is defined as the where the application's data lives:
+    /// `$XDG_DATA_HOME/amizade`. See [`crate::paths::PathResolver::app_data`].
+    ///
+    /// Personal folders use their `xdg-user-dirs` names, since they're
+    /// often localized or moved somewhere else.
     fn get_install_path(&self) -> String {
         match self {
             Self::Bin => "$HOME/.local/bin".to_owned(),
             Self::Books => "$APP_DATA/books".to_owned(),
             Self::Comics => "$APP_DATA/comics".to_owned(),
-            Self::Documents => "$HOME/Documents".to_owned(),
-            Self::Downloads => "$HOME/Downloads".to_owned(),
-            Self::Music => "$HOME/Music".to_owned(),
-            Self::Pictures => "$HOME/Pictures".to_owned(),
-            Self::Videos => "$HOME/Videos".to_owned(),
+            Self::Documents => "$XDG_DOCUMENTS_DIR".to_owned(),
+            Self::Downloads => "$XDG_DOWNLOAD_DIR".to_owned(),
+            Self::Music => "$XDG_MUSIC_DIR".to_owned(),
+            Self::Pictures => "$XDG_PICTURES_DIR".to_owned(),
+            Self::Videos => "$XDG_VIDEOS_DIR".to_owned(),
             Self::Wallpapers => "$XDG_DATA_HOME/wallpapers".to_owned(),
         }
     }
@@ -69,42 +322,157 @@ impl TryFrom<&str> for PersonalMedia {
     type Error = InstallTypeError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value.to_lowercase().as_str() {
-            "bin" => Ok(Self::Bin),
-            "books" => Ok(Self::Books),
-            "comics" => Ok(Self::Comics),
-            "documents" => Ok(Self::Documents),
-            "downloads" => Ok(Self::Downloads),
-            "music" => Ok(Self::Music),
-            "pictures" => Ok(Self::Pictures),
-            "videos" => Ok(Self::Videos),
-            "wallpapers" => Ok(Self::Wallpapers),
-            other => Err(InstallTypeError::NoMatchingInstallType(other.into())),
-        }
+        find_alias(Self::ALL, Self::aliases, value.to_lowercase().as_str())
     }
 }
 
 // styling
-#[derive(Debug, PartialEq, Eq)]
-
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Styling {
     ColorSchemes,
     Cursors,
     Emoticons,
     Fonts,
+    GRUBThemes,
     Icons,
+    PlymouthThemes,
+    SoundThemes,
     Themes,
+    CinnamonThemes,
+    GNOMEShellThemes,
+    GTK2Themes,
+    GTK3Themes,
+    KvantumThemes,
+    MetacityThemes,
+    OpenboxThemes,
+    Xfwm4Themes,
+}
+
+impl Styling {
+    pub const ALL: &'static [Self] = &[
+        Self::ColorSchemes,
+        Self::Cursors,
+        Self::Emoticons,
+        Self::Fonts,
+        Self::GRUBThemes,
+        Self::Icons,
+        Self::PlymouthThemes,
+        Self::SoundThemes,
+        Self::Themes,
+        Self::CinnamonThemes,
+        Self::GNOMEShellThemes,
+        Self::GTK2Themes,
+        Self::GTK3Themes,
+        Self::KvantumThemes,
+        Self::MetacityThemes,
+        Self::OpenboxThemes,
+        Self::Xfwm4Themes,
+    ];
+
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::ColorSchemes => &["color_schemes", "plasma_color_schemes"],
+            Self::Cursors => &["cursors"],
+            Self::Emoticons => &["emoticons"],
+            Self::Fonts => &["fonts"],
+            Self::GRUBThemes => &["grub_themes"],
+            Self::Icons => &["icons"],
+            Self::PlymouthThemes => &["plymouth_themes"],
+            Self::SoundThemes => &["sound_themes", "sounds"],
+            Self::Themes => &["themes"],
+            Self::CinnamonThemes => &["cinnamon_themes"],
+            Self::GNOMEShellThemes => &["gnome_shell_themes"],
+            Self::GTK2Themes => &["gtk2_themes"],
+            Self::GTK3Themes => &["gtk3_themes"],
+            Self::KvantumThemes => &["kvantum_themes"],
+            Self::MetacityThemes => &["metacity_themes"],
+            Self::OpenboxThemes => &["openbox_themes"],
+            Self::Xfwm4Themes => &["xfwm4_themes"],
+        }
+    }
 }
 
 impl InstallStrategy for Styling {
     fn get_install_path(&self) -> String {
         match self {
             Self::ColorSchemes => "$XDG_DATA_HOME/color-schemes".to_owned(),
-            Self::Cursors => "$HOME/.icons".to_owned(), // TODO: i think this should be `$HOME/.local/share/icons/` or `$XDG_DATA_HOME/icons`
+            Self::Cursors => "$XDG_DATA_HOME/icons".to_owned(),
             Self::Emoticons => "$XDG_DATA_HOME/emoticons".to_owned(),
-            Self::Fonts => "$HOME/.fonts".to_owned(), // TODO: `$XDG_DATA_HOME/fonts/`
-            Self::Icons => "$XDG_DATA_HOME/icons".to_owned(), // TODO: `$XDG_DATA_HOME/icons/`
-            Self::Themes => "$HOME/.themes".to_owned(), // TODO: `$XDG_DATA_HOME/themes/`
+            Self::Fonts => "$XDG_DATA_HOME/fonts".to_owned(),
+            Self::GRUBThemes => "/boot/grub/themes".to_owned(),
+            Self::Icons => "$XDG_DATA_HOME/icons".to_owned(),
+            Self::PlymouthThemes => "/usr/share/plymouth/themes".to_owned(),
+            Self::SoundThemes => "$XDG_DATA_HOME/sounds".to_owned(),
+            Self::KvantumThemes => "$XDG_CONFIG_HOME/Kvantum".to_owned(),
+            Self::Themes
+            | Self::CinnamonThemes
+            | Self::GNOMEShellThemes
+            | Self::GTK2Themes
+            | Self::GTK3Themes
+            | Self::MetacityThemes
+            | Self::OpenboxThemes
+            | Self::Xfwm4Themes => "$XDG_DATA_HOME/themes".to_owned(),
+        }
+    }
+
+    /// These used to live in dotfiles right in `$HOME`.
+    fn get_legacy_install_path(&self) -> Option<String> {
+        match self {
+            Self::Cursors | Self::Icons => Some("$HOME/.icons".to_owned()),
+            Self::Fonts => Some("$HOME/.fonts".to_owned()),
+            Self::Themes
+            | Self::CinnamonThemes
+            | Self::GNOMEShellThemes
+            | Self::GTK2Themes
+            | Self::GTK3Themes
+            | Self::MetacityThemes
+            | Self::OpenboxThemes
+            | Self::Xfwm4Themes => Some("$HOME/.themes".to_owned()),
+            Self::ColorSchemes
+            | Self::Emoticons
+            | Self::GRUBThemes
+            | Self::KvantumThemes
+            | Self::PlymouthThemes
+            | Self::SoundThemes => None,
+        }
+    }
+
+    /// Each theme flavour keeps its files in its own sub-directory of the
+    /// theme, like `Nordic/gtk-3.0`. Kvantum themes need a `.kvconfig` file.
+    fn check_layout(&self, item: &Path) -> Result<(), InstallTypeError> {
+        let expected = match self {
+            Self::CinnamonThemes => "cinnamon",
+            Self::GNOMEShellThemes => "gnome-shell",
+            Self::GTK2Themes => "gtk-2.0",
+            Self::GTK3Themes => "gtk-3.0",
+            Self::MetacityThemes => "metacity-1",
+            Self::OpenboxThemes => "openbox-3",
+            Self::Xfwm4Themes => "xfwm4",
+            Self::KvantumThemes => {
+                let has_kvconfig = std::fs::read_dir(item)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .any(|entry| entry.path().extension().is_some_and(|e| e == "kvconfig"));
+
+                return match has_kvconfig {
+                    true => Ok(()),
+                    false => Err(InstallTypeError::UnexpectedLayout {
+                        item: item.to_path_buf(),
+                        expected: "a `.kvconfig` file".into(),
+                    }),
+                };
+            }
+            _ => return Ok(()),
+        };
+
+        match item.join(expected).is_dir() {
+            true => Ok(()),
+            false => Err(InstallTypeError::UnexpectedLayout {
+                item: item.to_path_buf(),
+                expected: format!("a `{expected}` directory"),
+            }),
         }
     }
 }
@@ -113,23 +481,13 @@ impl TryFrom<&str> for Styling {
     type Error = InstallTypeError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "color_schemes" | "plasma_color_schemes" => Ok(Self::ColorSchemes),
-            "cursors" => Ok(Self::Cursors),
-            "emoticons" => Ok(Self::Emoticons),
-            "fonts" => Ok(Self::Fonts),
-            "icons" => Ok(Self::Icons),
-            "themes" | "gnome_shell_themes" | "cinnamon_themes" | "gtk2_themes" | "gtk3_themes"
-            | "metacity_themes" | "xfwm4_themes" | "openbox_themes" | "kvantum_themes" => {
-                Ok(Self::Themes)
-            }
-            other => Err(InstallTypeError::NoMatchingInstallType(other.into())),
-        }
+        find_alias(Self::ALL, Self::aliases, value)
     }
 }
 
 // wm themes
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WMThemes {
     CairoClockThemes,
     CinnamonApplets,
@@ -144,6 +502,38 @@ pub enum WMThemes {
     PekWMThemes,
 }
 
+impl WMThemes {
+    pub const ALL: &'static [Self] = &[
+        Self::CairoClockThemes,
+        Self::CinnamonApplets,
+        Self::CinnamonDesklets,
+        Self::CinnamonExtensions,
+        Self::EmeraldThemes,
+        Self::EnlightenmentBackgrounds,
+        Self::EnlightenmentThemes,
+        Self::FluxboxStyles,
+        Self::GNOMEShellExtensions,
+        Self::IceWMThemes,
+        Self::PekWMThemes,
+    ];
+
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::CairoClockThemes => &["cairo_clock_themes"],
+            Self::CinnamonApplets => &["cinnamon_applets"],
+            Self::CinnamonDesklets => &["cinnamon_desklets"],
+            Self::CinnamonExtensions => &["cinnamon_extensions"],
+            Self::EmeraldThemes => &["emerald_themes", "compiz_themes", "beryl_themes"],
+            Self::EnlightenmentBackgrounds => &["enlightenment_backgrounds"],
+            Self::EnlightenmentThemes => &["enlightenment_themes"],
+            Self::FluxboxStyles => &["fluxbox_styles"],
+            Self::GNOMEShellExtensions => &["gnome_shell_extensions"],
+            Self::IceWMThemes => &["icewm_themes"],
+            Self::PekWMThemes => &["pekwm_themes"],
+        }
+    }
+}
+
 impl InstallStrategy for WMThemes {
     fn get_install_path(&self) -> String {
         match self {
@@ -166,25 +556,13 @@ impl TryFrom<&str> for WMThemes {
     type Error = InstallTypeError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "cairo_clock_themes" => Ok(Self::CairoClockThemes),
-            "cinnamon_applets" => Ok(Self::CinnamonApplets),
-            "cinnamon_desklets" => Ok(Self::CinnamonDesklets),
-            "cinnamon_extensions" => Ok(Self::CinnamonExtensions),
-            "emerald_themes" | "compiz_themes" | "beryl_themes" => Ok(Self::EmeraldThemes),
-            "enlightenment_backgrounds" => Ok(Self::EnlightenmentBackgrounds),
-            "enlightenment_themes" => Ok(Self::EnlightenmentThemes),
-            "fluxbox_styles" => Ok(Self::FluxboxStyles),
-            "gnome_shell_extensions" => Ok(Self::GNOMEShellExtensions),
-            "icewm_themes" => Ok(Self::IceWMThemes),
-            "pekwm_themes" => Ok(Self::PekWMThemes),
-            other => Err(InstallTypeError::NoMatchingInstallType(other.into())),
-        }
+        find_alias(Self::ALL, Self::aliases, value)
     }
 }
 
 // kde themes
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QtGeneral {
     AmarokScripts,
     AuroraeThemes,
@@ -192,13 +570,53 @@ pub enum QtGeneral {
     KwinEffects,
     KwinScripts,
     KwinTabbox,
+    LatteLayouts,
     PlasmaDesktopthemes,
     PlasmaLookAndFeel,
     PlasmaPlasmoids,
     QtCurve,
+    SDDMThemes,
     YakuakeSkins,
 }
 
+impl QtGeneral {
+    pub const ALL: &'static [Self] = &[
+        Self::AmarokScripts,
+        Self::AuroraeThemes,
+        Self::DekoratorThemes,
+        Self::KwinEffects,
+        Self::KwinScripts,
+        Self::KwinTabbox,
+        Self::LatteLayouts,
+        Self::PlasmaDesktopthemes,
+        Self::PlasmaLookAndFeel,
+        Self::PlasmaPlasmoids,
+        Self::QtCurve,
+        Self::SDDMThemes,
+        Self::YakuakeSkins,
+    ];
+
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::AmarokScripts => &["amarok_scripts"],
+            Self::AuroraeThemes => &["aurorae_themes"],
+            Self::DekoratorThemes => &["dekorator_themes"],
+            Self::KwinEffects => &["kwin_effects"],
+            Self::KwinScripts => &["kwin_scripts"],
+            Self::KwinTabbox => &["kwin_tabbox"],
+            Self::LatteLayouts => &["latte_layouts"],
+            Self::PlasmaDesktopthemes => &["plasma_desktopthemes", "plasma5_desktopthemes"],
+            Self::PlasmaLookAndFeel => &["plasma_look_and_feel", "plasma5_look_and_feel"],
+            Self::PlasmaPlasmoids => {
+                &["plasma_plasmoids", "plasma4_plasmoids", "plasma5_plasmoids"]
+            }
+            Self::QtCurve => &["qtcurve"],
+            Self::SDDMThemes => &["sddm_themes"],
+            Self::YakuakeSkins => &["yakuake_skins"],
+        }
+    }
+}
+
 impl InstallStrategy for QtGeneral {
     fn get_install_path(&self) -> String {
         match self {
@@ -208,10 +626,12 @@ impl InstallStrategy for QtGeneral {
             Self::KwinEffects => "$XDG_DATA_HOME/kwin/effects".to_owned(),
             Self::KwinScripts => "$XDG_DATA_HOME/kwin/scripts".to_owned(),
             Self::KwinTabbox => "$XDG_DATA_HOME/kwin/tabbox".to_owned(),
+            Self::LatteLayouts => "$XDG_CONFIG_HOME/latte".to_owned(),
             Self::PlasmaDesktopthemes => "$XDG_DATA_HOME/plasma/desktoptheme".to_owned(),
             Self::PlasmaLookAndFeel => "$XDG_DATA_HOME/plasma/look-and-feel".to_owned(),
             Self::PlasmaPlasmoids => "$XDG_DATA_HOME/plasma/plasmoids".to_owned(),
             Self::QtCurve => "$XDG_DATA_HOME/QtCurve".to_owned(),
+            Self::SDDMThemes => "/usr/share/sddm/themes".to_owned(),
             Self::YakuakeSkins => "$KDEHOME/share/apps/yakuake/skins".to_owned(),
         }
     }
@@ -219,36 +639,71 @@ impl InstallStrategy for QtGeneral {
 
 impl TryFrom<&str> for QtGeneral {
     type Error = InstallTypeError;
+
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "amarok_scripts" => Ok(Self::AmarokScripts),
-            "aurorae_themes" => Ok(Self::AuroraeThemes),
-            "dekorator_themes" => Ok(Self::DekoratorThemes),
-            "kwin_effects" => Ok(Self::KwinEffects),
-            "kwin_scripts" => Ok(Self::KwinScripts),
-            "kwin_tabbox" => Ok(Self::KwinTabbox),
-            "plasma_desktopthemes" | "plasma5_desktopthemes" => Ok(Self::PlasmaDesktopthemes),
-            "plasma_look_and_feel" | "plasma5_look_and_feel" => Ok(Self::PlasmaLookAndFeel),
-            "plasma_plasmoids" | "plasma4_plasmoids" | "plasma5_plasmoids" => {
-                Ok(Self::PlasmaPlasmoids)
-            }
-            "qtcurve" => Ok(Self::QtCurve),
-            "yakuake_skins" => Ok(Self::YakuakeSkins),
-            other => Err(InstallTypeError::NoMatchingInstallType(other.into())),
-        }
+        find_alias(Self::ALL, Self::aliases, value)
     }
 }
 
 // application specific
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AppSpecific {
+    CajaScripts,
+    ConkyThemes,
+    DolphinServiceMenus,
     NautiliusScripts,
+    NemoActions,
+    NemoScripts,
+}
+
+impl AppSpecific {
+    pub const ALL: &'static [Self] = &[
+        Self::CajaScripts,
+        Self::ConkyThemes,
+        Self::DolphinServiceMenus,
+        Self::NautiliusScripts,
+        Self::NemoActions,
+        Self::NemoScripts,
+    ];
+
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::CajaScripts => &["caja_scripts"],
+            Self::ConkyThemes => &["conky_themes", "conky"],
+            Self::DolphinServiceMenus => &["dolphin_service_menus", "kde_service_menus"],
+            Self::NautiliusScripts => &["nautilus_scripts"],
+            Self::NemoActions => &["nemo_actions"],
+            Self::NemoScripts => &["nemo_scripts"],
+        }
+    }
 }
 
 impl InstallStrategy for AppSpecific {
     fn get_install_path(&self) -> String {
         match self {
+            Self::CajaScripts => "$XDG_CONFIG_HOME/caja/scripts".to_owned(),
+            Self::ConkyThemes => "$XDG_CONFIG_HOME/conky".to_owned(),
+            Self::DolphinServiceMenus => "$XDG_DATA_HOME/kio/servicemenus".to_owned(),
             Self::NautiliusScripts => "$XDG_DATA_HOME/nautilus/scripts".to_owned(),
+            Self::NemoActions => "$XDG_DATA_HOME/nemo/actions".to_owned(),
+            Self::NemoScripts => "$XDG_DATA_HOME/nemo/scripts".to_owned(),
         }
     }
+
+    /// Service menus moved when KDE Frameworks 5.85 came out.
+    fn get_legacy_install_path(&self) -> Option<String> {
+        match self {
+            Self::DolphinServiceMenus => Some("$XDG_DATA_HOME/kservices5/ServiceMenus".to_owned()),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<&str> for AppSpecific {
+    type Error = InstallTypeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        find_alias(Self::ALL, Self::aliases, value)
+    }
 }
@@ -1,3 +1,420 @@
-fn main() {
-    println!("Hello, world!");
+//! yoink-ocs: install things from `ocs://` links without a GUI.
+use clap::{Parser, Subcommand};
+use ocs_custodian::handler::{DownloadOptions, DownloadProgress};
+use ocs_custodian::installer::{ConflictPolicy, InstallReport, Installer};
+use ocs_custodian::manifest::{InstalledItem, Manifest};
+use ocs_custodian::parser::check_url;
+use ocs_custodian::plan::{FileAction, InstallPlan, PostInstallAction};
+use ocs_custodian::progress::Cancellation;
+use ocs_custodian::types::install_type::{InstallCategory, InstallType};
+use ocs_custodian::ParsedOcsUrl;
+use serde_json::{json, Value};
+
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::SystemTime;
+
+#[cfg(test)]
+mod tests;
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(version, about = "Installs themes, icons and more from ocs:// links.")]
+struct Cli {
+    /// Print JSON instead of text, for scripts.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Shows what's in a link.
+    Parse { link: String },
+    /// Installs what a link points to, after showing what will happen.
+    Install {
+        link: String,
+        /// What to do if it's already installed: skip, overwrite, keep-both or backup.
+        #[arg(long, value_parser = parse_policy)]
+        on_conflict: Option<ConflictPolicy>,
+        /// Only show what would happen.
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't ask before installing.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Downloads a link's file without installing it.
+    Download {
+        link: String,
+        /// The directory to save into.
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+    /// Lists everything that was installed.
+    List {
+        /// Only show one category, like `styling`.
+        #[arg(long)]
+        category: Option<String>,
+    },
+    /// Removes something that was installed, by its id from `list`.
+    Remove {
+        id: u64,
+        /// Remove files even if they changed since they were installed.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists the install types that links can use.
+    Types,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Commands::Parse { link } => parse(&link, cli.json),
+        Commands::Install {
+            link,
+            on_conflict,
+            dry_run,
+            yes,
+        } => install(&link, on_conflict, dry_run, yes, cli.json),
+        Commands::Download { link, output } => download(&link, output, cli.json),
+        Commands::List { category } => list(category.as_deref(), cli.json),
+        Commands::Remove { id, force } => remove(id, force, cli.json),
+        Commands::Types => types(cli.json),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            match cli.json {
+                true => println!("{}", error_json(e.as_ref())),
+                false => eprintln!("error: {e}"),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn error_json(error: &dyn Error) -> Value {
+    json!({ "error": error.to_string() })
+}
+
+fn parse_policy(policy: &str) -> Result<ConflictPolicy, String> {
+    ConflictPolicy::try_from(policy)
+        .map_err(|policy| format!("`{policy}` isn't skip, overwrite, keep-both or backup"))
+}
+
+fn parse(link: &str, json: bool) -> CliResult {
+    let link = check_url(link.to_owned())?;
+    let destination = Installer::from_env()?.destination(&link)?;
+
+    if json {
+        println!("{}", parse_json(&link, &destination)?);
+        return Ok(());
+    }
+
+    println!("Command:      {}", link.command);
+    println!("Download URL: {}", link.download_url);
+    println!("Install type: {}", describe_type(&link));
+    if let Some(filename) = &link.filename {
+        println!("Filename:     {filename}");
+    }
+    println!("Destination:  {}", destination.display());
+
+    Ok(())
+}
+
+/// What `parse --json` prints: the link's own JSON, plus where it would go.
+fn parse_json(link: &ParsedOcsUrl, destination: &Path) -> Result<Value, serde_json::Error> {
+    let mut value: Value = serde_json::from_str(&link.to_json()?)?;
+    value["destination"] = json!(destination);
+    Ok(value)
+}
+
+fn install(
+    link: &str,
+    on_conflict: Option<ConflictPolicy>,
+    dry_run: bool,
+    yes: bool,
+    json: bool,
+) -> CliResult {
+    let link = check_url(link.to_owned())?;
+    let mut installer = Installer::from_env()?;
+    if let Some(policy) = on_conflict {
+        installer.conflict_policy = policy;
+    }
+
+    let plan = installer.plan(&link, progress_printer("Checking", json))?;
+    if !json {
+        print_plan(&plan);
+    }
+
+    if dry_run {
+        plan.discard(&installer.resolver);
+        if json {
+            println!("{}", install_json(&plan, None));
+        }
+        return Ok(());
+    }
+
+    if !yes {
+        let confirmed = confirm("Install?");
+        if !matches!(confirmed, Ok(true)) {
+            plan.discard(&installer.resolver);
+        }
+        if !confirmed? {
+            return Err("Not installing, since the install wasn't confirmed.".into());
+        }
+    }
+
+    let report = installer.install_plan(&plan, &Cancellation::new(), |_| ())?;
+    match json {
+        true => println!("{}", install_json(&plan, Some(&report))),
+        false => print_report(&report),
+    }
+
+    Ok(())
+}
+
+/// What `install --json` prints. The report is `null` if nothing was
+/// installed, like for a dry run.
+fn install_json(plan: &InstallPlan, report: Option<&InstallReport>) -> Value {
+    json!({ "plan": plan, "report": report })
+}
+
+fn download(link: &str, output: PathBuf, json: bool) -> CliResult {
+    let link = check_url(link.to_owned())?;
+    let path = link.download(
+        &output,
+        &DownloadOptions::default(),
+        progress_printer("Downloading", json),
+    )?;
+
+    match json {
+        true => println!("{}", json!({ "path": path })),
+        false => println!("Saved to {}", path.display()),
+    }
+
+    Ok(())
+}
+
+fn list(category: Option<&str>, json: bool) -> CliResult {
+    let installer = Installer::from_env()?;
+    let manifest = Manifest::load(&installer.resolver)?;
+
+    let items: Vec<&InstalledItem> = match category {
+        Some(category) => {
+            let category = InstallCategory::try_from(category)
+                .map_err(|_| format!("`{category}` isn't an install category"))?;
+            manifest.in_category(category).collect()
+        }
+        None => manifest.items().iter().collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string(&items)?);
+        return Ok(());
+    }
+
+    if items.is_empty() {
+        println!("Nothing is installed yet.");
+    }
+    for item in items {
+        println!(
+            "{:>4}  {}  ({}, installed {})",
+            item.id,
+            item.name(),
+            item.link.install_type_alias,
+            ago(item.installed_at)
+        );
+        println!("      {}", item.destination.display());
+    }
+
+    Ok(())
+}
+
+fn remove(id: u64, force: bool, json: bool) -> CliResult {
+    let installer = Installer::from_env()?;
+    let report = installer.uninstall(id, force)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&report)?);
+        return Ok(());
+    }
+
+    println!("Removed {} file(s).", report.removed.len());
+    for dir in &report.kept {
+        println!("Kept {}, since something else is in it.", dir.display());
+    }
+
+    Ok(())
+}
+
+fn types(json: bool) -> CliResult {
+    if json {
+        println!("{}", types_json());
+        return Ok(());
+    }
+
+    let mut category = None;
+    for entry in InstallType::registry() {
+        if category != Some(entry.category) {
+            category = Some(entry.category);
+            println!("{}", entry.category);
+        }
+        println!("  {:<24} {}", entry.alias, entry.destination);
+    }
+
+    Ok(())
+}
+
+/// What `types --json` prints.
+fn types_json() -> Value {
+    InstallType::registry()
+        .iter()
+        .map(|entry| {
+            json!({
+                "alias": entry.alias,
+                "category": entry.category.to_string(),
+                "install_type": entry.install_type,
+                "destination": entry.destination,
+            })
+        })
+        .collect()
+}
+
+/// Like `icons (Styling)`.
+fn describe_type(link: &ParsedOcsUrl) -> String {
+    format!(
+        "{} ({})",
+        link.install_type_alias,
+        link.install_type.category()
+    )
+}
+
+fn print_plan(plan: &InstallPlan) {
+    let host = plan.link.download_url.host_str().unwrap_or("somewhere");
+    println!("From {host}, as {}", describe_type(&plan.link));
+    println!("Into {}", plan.destination.display());
+
+    for conflict in &plan.conflicts {
+        let what = match (conflict.policy, &conflict.moved_to) {
+            (ConflictPolicy::Skip, _) => "will be kept, and the new one skipped".to_owned(),
+            (ConflictPolicy::Overwrite, _) => "will be replaced".to_owned(),
+            (ConflictPolicy::KeepBoth, Some(to)) => {
+                format!("will be kept, and the new one goes to {}", to.display())
+            }
+            (ConflictPolicy::Backup, Some(to)) => format!("will be moved to {}", to.display()),
+            (_, None) => "is in the way".to_owned(),
+        };
+        println!("  ! {} {what}", conflict.existing.display());
+    }
+
+    for file in &plan.files {
+        let mark = match file.action {
+            FileAction::Create => '+',
+            FileAction::Overwrite => '~',
+        };
+        println!("  {mark} {} ({})", file.path.display(), size(file.size));
+    }
+
+    println!(
+        "{} in {} file(s), from a {} download",
+        size(plan.install_size),
+        plan.files.len(),
+        size(plan.download_size)
+    );
+    for action in &plan.post_install {
+        match action {
+            PostInstallAction::UpdateFontCache => println!("Then, the font cache is updated."),
+        }
+    }
+}
+
+fn print_report(report: &InstallReport) {
+    for item in &report.items {
+        println!("Installed {}", item.display());
+    }
+    if report.items.is_empty() {
+        println!("Nothing was installed.");
+    }
+    for warning in &report.warnings {
+        eprintln!("warning: {warning}");
+    }
+}
+
+/// Asks a yes or no question. Without a terminal to ask on, that's an error,
+/// so scripts have to say `--yes` up front.
+fn confirm(question: &str) -> io::Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Err(io::Error::other(
+            "There's nobody to ask before installing. Pass --yes to install anyway.",
+        ));
+    }
+
+    eprint!("{question} [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Shows download progress on stderr, unless we're printing JSON.
+fn progress_printer(verb: &'static str, json: bool) -> impl FnMut(DownloadProgress) {
+    let show = !json && io::stderr().is_terminal();
+
+    move |progress| {
+        if !show {
+            return;
+        }
+
+        match progress.total {
+            Some(total) if total > 0 => {
+                eprint!("\r{verb}... {}%", progress.downloaded * 100 / total)
+            }
+            _ => eprint!("\r{verb}... {}", size(progress.downloaded)),
+        }
+        if progress.total == Some(progress.downloaded) {
+            eprintln!();
+        }
+    }
+}
+
+fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+fn ago(time: SystemTime) -> String {
+    let seconds = SystemTime::now()
+        .duration_since(time)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    match seconds {
+        0..=59 => "just now".to_owned(),
+        60..=3599 => format!("{} minute(s) ago", seconds / 60),
+        3600..=86399 => format!("{} hour(s) ago", seconds / 3600),
+        _ => format!("{} day(s) ago", seconds / 86400),
+    }
 }
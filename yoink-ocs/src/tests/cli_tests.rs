@@ -0,0 +1,147 @@
+use crate::{error_json, install_json, parse_json, types_json, Cli, Commands};
+use clap::Parser;
+use ocs_custodian::installer::{ConflictPolicy, InstallReport};
+use ocs_custodian::parser::check_url;
+use ocs_custodian::plan::InstallPlan;
+use std::path::{Path, PathBuf};
+
+const LINK: &str = "ocs://install?url=https%3A%2F%2Fexample.com%2Fcursor.png&type=icons";
+
+fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+    Cli::try_parse_from(std::iter::once("yoink-ocs").chain(args.iter().copied()))
+}
+
+fn plan() -> InstallPlan {
+    let link = check_url(LINK.to_owned()).unwrap();
+    InstallPlan {
+        install_type: link.install_type,
+        link,
+        destination: PathBuf::from("/home/me/.local/share/icons"),
+        conflict_policy: ConflictPolicy::Backup,
+        items: vec![PathBuf::from("/home/me/.local/share/icons/cursor.png")],
+        files: Vec::new(),
+        conflicts: Vec::new(),
+        download_size: 15,
+        install_size: 15,
+        post_install: Vec::new(),
+        contents: Vec::new(),
+        staged: PathBuf::from("/home/me/.local/share/amizade/staging/plan/cursor.png"),
+        sha256: "abc".to_owned(),
+        legacy: Vec::new(),
+    }
+}
+
+#[test]
+fn install_arguments() {
+    let cli = parse(&["install", LINK]).unwrap();
+    assert!(!cli.json);
+    assert!(matches!(
+        cli.command,
+        Commands::Install { link, on_conflict: None, dry_run: false, yes: false } if link == LINK
+    ));
+
+    let cli = parse(&[
+        "--json",
+        "install",
+        "-y",
+        "--on-conflict",
+        "keep-both",
+        LINK,
+    ])
+    .unwrap();
+    assert!(cli.json);
+    assert!(matches!(
+        cli.command,
+        Commands::Install {
+            on_conflict: Some(ConflictPolicy::KeepBoth),
+            yes: true,
+            ..
+        }
+    ));
+
+    // --json works after the subcommand too
+    let cli = parse(&["install", "--dry-run", LINK, "--json"]).unwrap();
+    assert!(cli.json);
+    assert!(matches!(
+        cli.command,
+        Commands::Install { dry_run: true, .. }
+    ));
+}
+
+#[test]
+fn bad_arguments() {
+    assert!(parse(&["install"]).is_err());
+    assert!(parse(&["install", "--on-conflict", "explode", LINK]).is_err());
+    assert!(parse(&["remove", "three"]).is_err());
+    assert!(parse(&["frobnicate"]).is_err());
+}
+
+#[test]
+fn other_arguments() {
+    assert!(matches!(
+        parse(&["download", LINK]).unwrap().command,
+        Commands::Download { output, .. } if output == Path::new(".")
+    ));
+    assert!(matches!(
+        parse(&["list", "--category", "styling"]).unwrap().command,
+        Commands::List { category: Some(category) } if category == "styling"
+    ));
+    assert!(matches!(
+        parse(&["remove", "3", "--force"]).unwrap().command,
+        Commands::Remove { id: 3, force: true }
+    ));
+    assert!(matches!(
+        parse(&["types"]).unwrap().command,
+        Commands::Types
+    ));
+}
+
+#[test]
+fn install_json_shapes() {
+    let plan = plan();
+
+    let dry_run = install_json(&plan, None);
+    assert_eq!(
+        dry_run["plan"]["destination"],
+        "/home/me/.local/share/icons"
+    );
+    assert_eq!(dry_run["plan"]["conflict_policy"], "backup");
+    assert_eq!(dry_run["plan"]["download_size"], 15);
+    assert!(dry_run["report"].is_null());
+
+    let report = InstallReport {
+        install_type: plan.install_type,
+        destination: plan.destination.clone(),
+        items: plan.items.clone(),
+        files: plan.items.clone(),
+        conflicts: Vec::new(),
+        warnings: vec!["fc-cache failed".to_owned()],
+    };
+    let installed = install_json(&plan, Some(&report));
+    assert_eq!(
+        installed["report"]["items"][0],
+        "/home/me/.local/share/icons/cursor.png"
+    );
+    assert_eq!(installed["report"]["warnings"][0], "fc-cache failed");
+}
+
+#[test]
+fn other_json_shapes() {
+    let link = check_url(LINK.to_owned()).unwrap();
+    let parsed = parse_json(&link, Path::new("/home/me/.local/share/icons")).unwrap();
+    assert_eq!(parsed["download_url"], "https://example.com/cursor.png");
+    assert_eq!(parsed["destination"], "/home/me/.local/share/icons");
+
+    let types = types_json();
+    let icons = types
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["alias"] == "icons")
+        .unwrap();
+    assert_eq!(icons["category"], "Styling");
+    assert!(icons["destination"].is_string());
+
+    let error = error_json(&*Box::<dyn std::error::Error>::from("it broke"));
+    assert_eq!(error, serde_json::json!({ "error": "it broke" }));
+}